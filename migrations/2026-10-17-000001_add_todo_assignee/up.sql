@@ -0,0 +1,2 @@
+-- Adds the person responsible for a todo
+ALTER TABLE todos ADD COLUMN assignee TEXT;
@@ -0,0 +1,33 @@
+CREATE TABLE todos_new (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    project_id        INTEGER NOT NULL,
+    parent_id         INTEGER,
+
+    title             TEXT    NOT NULL,
+    description       TEXT,
+    status            INTEGER NOT NULL,
+    priority          INTEGER NOT NULL,
+    due_date          TIMESTAMP,
+    estimated_minutes INTEGER,
+    location          TEXT,
+    url               TEXT,
+    created_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    completed_at      TIMESTAMP,
+    assignee          TEXT,
+
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id)  REFERENCES todos(id)    ON DELETE CASCADE
+);
+
+INSERT INTO todos_new (
+    id, project_id, parent_id, title, description, status, priority, due_date,
+    estimated_minutes, location, url, created_at, updated_at, completed_at, assignee
+)
+SELECT
+    id, project_id, parent_id, title, description, status, priority, due_date,
+    estimated_minutes, location, url, created_at, updated_at, completed_at, assignee
+FROM todos;
+
+DROP TABLE todos;
+ALTER TABLE todos_new RENAME TO todos;
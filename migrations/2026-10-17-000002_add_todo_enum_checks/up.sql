@@ -0,0 +1,35 @@
+-- Adds CHECK constraints so only known status and priority codes can be stored.
+-- SQLite can't add constraints to an existing table, so the table is recreated.
+CREATE TABLE todos_new (
+    id                INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    project_id        INTEGER NOT NULL,
+    parent_id         INTEGER,
+
+    title             TEXT    NOT NULL,
+    description       TEXT,
+    status            INTEGER NOT NULL CHECK (status BETWEEN 0 AND 5),    -- See TodoStatus
+    priority          INTEGER NOT NULL CHECK (priority BETWEEN 0 AND 3),  -- See TodoPriority
+    due_date          TIMESTAMP,
+    estimated_minutes INTEGER,
+    location          TEXT,
+    url               TEXT,
+    created_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at        TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    completed_at      TIMESTAMP,
+    assignee          TEXT,
+
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+    FOREIGN KEY (parent_id)  REFERENCES todos(id)    ON DELETE CASCADE
+);
+
+INSERT INTO todos_new (
+    id, project_id, parent_id, title, description, status, priority, due_date,
+    estimated_minutes, location, url, created_at, updated_at, completed_at, assignee
+)
+SELECT
+    id, project_id, parent_id, title, description, status, priority, due_date,
+    estimated_minutes, location, url, created_at, updated_at, completed_at, assignee
+FROM todos;
+
+DROP TABLE todos;
+ALTER TABLE todos_new RENAME TO todos;
@@ -0,0 +1,2 @@
+-- Tracks whether a project is active, archived or paused. See ProjectStatus
+ALTER TABLE projects ADD COLUMN status INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE todos DROP COLUMN blocked_by_id;
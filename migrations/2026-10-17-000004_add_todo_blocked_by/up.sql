@@ -0,0 +1,2 @@
+-- The todo that is blocking this one
+ALTER TABLE todos ADD COLUMN blocked_by_id INTEGER REFERENCES todos(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+-- Comma separated labels, e.g. `work,urgent`
+ALTER TABLE todos ADD COLUMN tags TEXT;
@@ -0,0 +1,3 @@
+-- When the project was archived, e.g. by `tudu close project`. Null while it is not archived
+ALTER TABLE projects ADD COLUMN archived_at TIMESTAMP;
+UPDATE projects SET archived_at = updated_at WHERE status = 1;
@@ -0,0 +1,2 @@
+-- How long the todo actually took, to compare against estimated_minutes
+ALTER TABLE todos ADD COLUMN actual_minutes INTEGER;
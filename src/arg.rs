@@ -1,51 +1,285 @@
-use chrono::{NaiveDateTime, ParseError as ChronoError, Utc};
-use clap::{Arg, ArgAction, ArgMatches, builder::NonEmptyStringValueParser, value_parser};
-use std::str::FromStr;
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc, Weekday};
+use clap::{
+    Arg, ArgAction, ArgMatches, builder::NonEmptyStringValueParser, parser::ValueSource,
+    value_parser,
+};
+use diesel::{
+    QueryDsl, RunQueryDsl, SqliteConnection,
+    backend::Backend,
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Integer,
+    sqlite::Sqlite,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf, str::FromStr};
 use strum::EnumProperty;
 use url::{ParseError as UrlError, Url};
 
 use crate::{
     error::{TuduError, TuduResult},
-    todo::sql::{TodoPriority, TodoStatus},
+    project::{query::ProjectQuery, sql::ProjectStatus},
+    schema::projects::dsl as projects_dsl,
+    todo::{
+        command::SortField,
+        sql::{TodoPriority, TodoStatus},
+    },
 };
 
+/// The id of a project, kept distinct from other ids so they can't be mixed up.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+)]
+#[diesel(sql_type = Integer)]
+pub struct ProjectId(pub i32);
+
+/// The id of a todo, kept distinct from other ids so they can't be mixed up.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+)]
+#[diesel(sql_type = Integer)]
+pub struct TodoId(pub i32);
+
+impl fmt::Display for ProjectId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for TodoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromSql<Integer, Sqlite> for ProjectId {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        i32::from_sql(bytes).map(ProjectId)
+    }
+}
+
+impl ToSql<Integer, Sqlite> for ProjectId {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(self.0);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Integer, Sqlite> for TodoId {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        i32::from_sql(bytes).map(TodoId)
+    }
+}
+
+impl ToSql<Integer, Sqlite> for TodoId {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(self.0);
+        Ok(IsNull::No)
+    }
+}
+
+/// Where a project id given to a command came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectIdSource {
+    /// The id was passed on the command line.
+    Flag,
+    /// The id was read from the `PROJECT_ID` of this config file.
+    ConfigFile(PathBuf),
+    /// Neither was given, so the only active project, with this name, was used.
+    AutoSelected(String),
+}
+
 // Custom wrapper types for validation
-#[derive(Debug, Clone)]
-pub struct ValidUrl(pub Url);
+/// A url given to a todo: either a full url or a relative reference like `/issues/123`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidUrl {
+    /// A url with a scheme, e.g. `https://example.com`.
+    Absolute(Url),
+    /// A path-like reference without a scheme, e.g. `/issues/123` or `PROJ-456`.
+    Relative(String),
+}
+
+impl fmt::Display for ValidUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidUrl::Absolute(url) => write!(f, "{}", url),
+            ValidUrl::Relative(reference) => write!(f, "{}", reference),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct ValidDateTime(pub NaiveDateTime);
 
+/// Comma separated tags with the whitespace, empty entries and duplicates removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidTags(pub Vec<String>);
+
+impl fmt::Display for ValidTags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(","))
+    }
+}
+
+impl FromStr for ValidTags {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in s.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+            if !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+        if tags.is_empty() {
+            Err(format!(
+                "Invalid tags: {}. Try something like work,urgent",
+                s
+            ))
+        } else {
+            Ok(ValidTags(tags))
+        }
+    }
+}
+
 impl FromStr for ValidUrl {
     type Err = UrlError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Url::parse(s).map(ValidUrl)
+        match Url::parse(s) {
+            Ok(url) => Ok(ValidUrl::Absolute(url)),
+            Err(err) => {
+                let relative_regex =
+                    regex::Regex::new(r"^[/A-Za-z0-9._~:@!$&'()*+,;=%-]+$").unwrap();
+                if relative_regex.is_match(s) {
+                    Ok(ValidUrl::Relative(s.to_string()))
+                } else {
+                    Err(err)
+                }
+            }
+        }
     }
 }
 
-/// Enforces errors to just be a default value of now
+// Resolves shorthands like `eod` or `friday` to the end of the matching day, relative to `now`
+fn parse_date_shorthand(s: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let today = now.date();
+    let day = match s.trim().to_lowercase().as_str() {
+        "eod" => today,
+        "eow" => {
+            let days_ahead = (Weekday::Fri.num_days_from_monday() + 7
+                - today.weekday().num_days_from_monday())
+                % 7;
+            today.checked_add_days(Days::new(days_ahead as u64))?
+        }
+        "eom" => {
+            let first_of_next_month = if today.month() == 12 {
+                NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)?
+            };
+            first_of_next_month.pred_opt()?
+        }
+        "eoy" => NaiveDate::from_ymd_opt(today.year(), 12, 31)?,
+        other => {
+            let weekday = other.parse::<Weekday>().ok()?;
+            // Always the next occurrence, so naming today's weekday means a week from now
+            let days_ahead =
+                (weekday.num_days_from_monday() + 6 - today.weekday().num_days_from_monday()) % 7
+                    + 1;
+            today.checked_add_days(Days::new(days_ahead as u64))?
+        }
+    };
+    Some(day.and_time(NaiveTime::from_hms_opt(23, 59, 59)?))
+}
+
+// Resolves `now`, `today`, `tomorrow`, `yesterday`, `next week` and offsets like `+2d`, `+3h`
+// or `+1w` relative to `now`. Named days mean the end of that day.
+fn parse_relative_datetime(s: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let end_of_day = |date: NaiveDate| date.and_hms_opt(23, 59, 59);
+    match s.trim().to_lowercase().as_str() {
+        "now" => Some(now),
+        "today" => end_of_day(now.date()),
+        "tomorrow" => end_of_day(now.date().succ_opt()?),
+        "yesterday" => end_of_day(now.date().pred_opt()?),
+        "next week" => now.checked_add_signed(TimeDelta::try_weeks(1)?),
+        other => {
+            let offset = other.strip_prefix('+')?;
+            let unit = offset.chars().last()?;
+            let amount: u32 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+            let delta = match unit {
+                'h' => TimeDelta::try_hours(amount.into())?,
+                'd' => TimeDelta::try_days(amount.into())?,
+                'w' => TimeDelta::try_weeks(amount.into())?,
+                _ => return None,
+            };
+            now.checked_add_signed(delta)
+        }
+    }
+}
+
+// Offsets and plain words are clearly meant as dates, so failing to read them is an error
+// rather than a silent fallback to now
+fn looks_like_relative_datetime(s: &str) -> bool {
+    let s = s.trim();
+    s.starts_with('+') || (!s.is_empty() && s.chars().all(|c| c.is_ascii_alphabetic() || c == ' '))
+}
+
+/// Falls back to now for unrecognized dates, unless they look like a mistyped relative date
 impl FromStr for ValidDateTime {
-    type Err = ChronoError;
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let now = Utc::now().naive_utc();
+        if let Some(date) = parse_date_shorthand(s, now).or_else(|| parse_relative_datetime(s, now))
+        {
+            return Ok(ValidDateTime(date));
+        }
+
         // Try multiple datetime formats
         if let Ok(date) = NaiveDateTime::parse_from_str(s, "%m/%d/%y %I:%M%p") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%m/%d/%Y %H:%M:%S") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%m/%d/%Y %H:%M") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%d/%m/%Y %H:%M:%S") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
         } else if let Ok(date) = NaiveDateTime::parse_from_str(s, "%d/%m/%Y %H:%M") {
-            return Ok(ValidDateTime(date));
+            Ok(ValidDateTime(date))
+        } else if looks_like_relative_datetime(s) {
+            Err(format!(
+                "Invalid date: {}. Try tomorrow, +2d, +3h, +1w, eow or a date like 2024-03-20 09:00",
+                s
+            ))
         } else {
-            let now = Utc::now().naive_utc();
             Ok(ValidDateTime(now))
         }
     }
@@ -71,6 +305,18 @@ impl FromStr for ValidHexColor {
     }
 }
 
+/// The layout used to print a list of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// One compact line per item.
+    #[default]
+    Normal,
+    /// A table with every field, sized to the terminal.
+    Wide,
+    /// One plain line per item without colors, for scripts.
+    Minimal,
+}
+
 #[derive(EnumProperty)]
 pub enum TuduArg {
     #[strum(props(
@@ -123,7 +369,7 @@ pub enum TuduArg {
 
     #[strum(props(
         name = "due_date",
-        about = "The deadline for this task. Don't miss it! Default to `now`"
+        about = "The deadline for this task. Don't miss it! Also accepts eod, eow (Friday), eom, eoy, a weekday name like monday, today, tomorrow, yesterday, next week or offsets like +2h, +3d, +1w. Default to `now`"
     ))]
     DueDate,
     #[strum(props(
@@ -132,6 +378,12 @@ pub enum TuduArg {
     ))]
     EstimatedMinutes,
 
+    #[strum(props(
+        name = "actual_minutes",
+        about = "How long this actually took, in minutes. Compared against the estimate"
+    ))]
+    ActualMinutes,
+
     #[strum(props(
         name = "location",
         about = "Where does this task need to happen? (e.g., 'Office', 'Home')."
@@ -144,8 +396,349 @@ pub enum TuduArg {
     ))]
     Url,
 
-    #[strum(props(name = "include_done", about = "Include the items with status done"))]
+    #[strum(props(
+        name = "include_done",
+        about = "Include the items with status done",
+        negation = "no_include_done"
+    ))]
     IncludeDone,
+
+    #[strum(props(
+        name = "has_overdue",
+        about = "Only show projects with at least one overdue todo"
+    ))]
+    HasOverdue,
+
+    #[strum(props(
+        name = "no_overdue",
+        about = "Only show projects without any overdue todos. A quick health check!"
+    ))]
+    NoOverdue,
+
+    #[strum(props(
+        name = "output",
+        about = "Write the output to this file instead of the terminal"
+    ))]
+    Output,
+
+    #[strum(props(name = "assignee", about = "Who is on the hook for this task?"))]
+    Assignee,
+
+    #[strum(props(
+        name = "tags",
+        about = "Comma separated labels for this task, e.g. work,urgent"
+    ))]
+    Tags,
+
+    #[strum(props(name = "tag", about = "Only show the tasks with this tag"))]
+    Tag,
+
+    #[strum(props(
+        name = "assigned_to_me",
+        about = "Only show the tasks assigned to you. Set through the `TUDU_USER` env variable"
+    ))]
+    AssignedToMe,
+
+    #[strum(props(name = "key", about = "The config key to set (e.g., PROJECT_ID)"))]
+    Key,
+
+    #[strum(props(name = "value", about = "The value for the config key"))]
+    Value,
+
+    #[strum(props(
+        name = "global",
+        about = "Use the global config instead of the `.tudu` in the current directory"
+    ))]
+    Global,
+
+    #[strum(props(
+        name = "show_id_only",
+        about = "Print only the ids, one per line. Great for scripts, e.g. `tudu list todo --show_id_only | xargs -n1 tudu close todo`"
+    ))]
+    ShowIdOnly,
+
+    #[strum(props(
+        name = "next_color",
+        about = "Cycle your project to the next color in the palette. No hex codes needed!"
+    ))]
+    NextColor,
+
+    #[strum(props(
+        name = "since_created",
+        about = "Only show tasks created within the last N days"
+    ))]
+    SinceCreated,
+
+    #[strum(props(
+        name = "older_than",
+        about = "Only show tasks created more than N days ago. Dust them off!"
+    ))]
+    OlderThan,
+
+    #[strum(props(
+        name = "copy_from",
+        about = "Start from a copy of this todo. Any other args you pass win."
+    ))]
+    CopyFrom,
+
+    #[strum(props(name = "format", about = "How should the results be laid out?"))]
+    Format,
+
+    #[strum(props(
+        name = "with_last_activity",
+        about = "Show the most recently updated todo of each project"
+    ))]
+    WithLastActivity,
+
+    #[strum(props(name = "reason", about = "Why is this being closed?"))]
+    Reason,
+
+    #[strum(props(name = "week", about = "Lay out the todos due this week by day"))]
+    Week,
+
+    #[strum(props(
+        name = "start_date",
+        about = "The first day of the calendar, e.g. 2025-06-02. Defaults to today"
+    ))]
+    StartDate,
+
+    #[strum(props(name = "days", about = "How many days the calendar should span"))]
+    Days,
+
+    #[strum(props(
+        name = "project_status",
+        about = "Is the project active, archived or paused?"
+    ))]
+    ProjectStatus,
+
+    #[strum(props(name = "blocked_by", about = "The ID of the todo that is in the way"))]
+    BlockedBy,
+
+    #[strum(props(name = "yes", about = "Skip the confirmation prompt"))]
+    Yes,
+
+    #[strum(props(
+        name = "overdue_first",
+        about = "List overdue todos before everything else"
+    ))]
+    OverdueFirst,
+
+    #[strum(props(
+        name = "overdue",
+        about = "Only show the open tasks that are past their due date"
+    ))]
+    Overdue,
+
+    #[strum(props(name = "query", about = "The text to look for"))]
+    Query,
+
+    #[strum(props(name = "case_sensitive", about = "Match upper and lower case exactly"))]
+    CaseSensitive,
+
+    #[strum(props(
+        name = "group_by_day",
+        about = "Group the todos under the day they are due"
+    ))]
+    GroupByDay,
+
+    #[strum(props(
+        name = "strip_markdown",
+        about = "Remove markdown formatting from the title"
+    ))]
+    StripMarkdown,
+
+    #[strum(props(
+        name = "no_strip_markdown",
+        about = "Keep markdown in the title even when it looks like markdown"
+    ))]
+    NoStripMarkdown,
+
+    #[strum(props(
+        name = "cascade",
+        about = "Also close the open subtodos of the closed todo (default)",
+        negation = "no_cascade"
+    ))]
+    Cascade,
+
+    #[strum(props(
+        name = "with_progress",
+        about = "Show a progress bar of the done todos of each project"
+    ))]
+    WithProgress,
+
+    #[strum(props(
+        name = "set_default",
+        about = "Set the project of the todo as the default project in ./.tudu"
+    ))]
+    SetDefault,
+
+    #[strum(props(
+        name = "check",
+        about = "List the pending migrations without running them, failing if there are any"
+    ))]
+    Check,
+
+    #[strum(props(
+        name = "allow_past_due",
+        about = "Don't warn when the due date is in the past"
+    ))]
+    AllowPastDue,
+
+    #[strum(props(
+        name = "force_status",
+        about = "Set the status even when moving from the current status isn't normally allowed"
+    ))]
+    ForceStatus,
+
+    #[strum(props(
+        name = "estimate_per_day",
+        about = "Show the estimated minutes of the open todos spread over the working days until the last due date"
+    ))]
+    EstimatePerDay,
+
+    #[strum(props(
+        name = "init_config",
+        about = "Write the new project's id to .tudu in the current directory"
+    ))]
+    InitConfig,
+
+    #[strum(props(
+        name = "force",
+        about = "Go ahead even when tudu would normally refuse, like overwriting an existing value"
+    ))]
+    Force,
+
+    #[strum(props(
+        name = "reopen",
+        about = "Move a closed todo back to ToDo before applying the other changes"
+    ))]
+    Reopen,
+
+    #[strum(props(
+        name = "debug_display",
+        about = "Show the raw field values without any styling, handy for reporting issues"
+    ))]
+    DebugDisplay,
+
+    #[strum(props(
+        name = "with_todo_counts",
+        about = "Show how many todos each project has in every status"
+    ))]
+    WithTodoCounts,
+
+    #[strum(props(
+        name = "created_after",
+        about = "Only show the projects created after this date. Projects without a created date are left out"
+    ))]
+    CreatedAfter,
+
+    #[strum(props(
+        name = "created_before",
+        about = "Only show the projects created before this date. Projects without a created date are left out"
+    ))]
+    CreatedBefore,
+
+    #[strum(props(
+        name = "updated_after",
+        about = "Only show the projects updated after this date. Projects without an updated date are left out"
+    ))]
+    UpdatedAfter,
+
+    #[strum(props(
+        name = "updated_before",
+        about = "Only show the projects updated before this date. Projects without an updated date are left out"
+    ))]
+    UpdatedBefore,
+
+    #[strum(props(
+        name = "sort_by",
+        about = "The field to order the tasks by. Without it they are ordered by priority, most urgent first"
+    ))]
+    SortBy,
+
+    #[strum(props(
+        name = "descending",
+        about = "Sort from the highest value down, e.g. the latest due date first"
+    ))]
+    Descending,
+
+    #[strum(props(
+        name = "limit",
+        about = "The most tasks to show at once, 50 by default"
+    ))]
+    Limit,
+
+    #[strum(props(
+        name = "offset",
+        about = "Skip this many tasks before showing any, to page through long lists"
+    ))]
+    Offset,
+
+    #[strum(props(
+        name = "project_name",
+        about = "The name of a new project to create and use"
+    ))]
+    ProjectName,
+
+    #[strum(props(
+        name = "include_archived",
+        about = "Also show the archived projects, like the ones closed with `close project`"
+    ))]
+    IncludeArchived,
+
+    #[strum(props(
+        name = "due_before",
+        about = "Only show the tasks due before this date. Tasks without a due date are left out"
+    ))]
+    DueBefore,
+
+    #[strum(props(
+        name = "due_after",
+        about = "Only show the tasks due after this date. Tasks without a due date are left out"
+    ))]
+    DueAfter,
+
+    #[strum(props(
+        name = "with_note",
+        about = "A note about what was done, added to the description with the completion time"
+    ))]
+    WithNote,
+
+    #[strum(props(name = "with_url", about = "Only show the todos that have a url"))]
+    WithUrl,
+
+    #[strum(props(name = "without_url", about = "Only show the todos without a url"))]
+    WithoutUrl,
+
+    #[strum(props(
+        name = "with_description",
+        about = "Only show the todos that have a description"
+    ))]
+    WithDescription,
+
+    #[strum(props(
+        name = "without_description",
+        about = "Only show the todos without a description"
+    ))]
+    WithoutDescription,
+
+    #[strum(props(
+        name = "clear_description",
+        about = "Remove the description of the todo"
+    ))]
+    ClearDescription,
+
+    #[strum(props(
+        name = "with_location",
+        about = "Only show the todos that have a location"
+    ))]
+    WithLocation,
+
+    #[strum(props(
+        name = "without_location",
+        about = "Only show the todos without a location"
+    ))]
+    WithoutLocation,
 }
 
 impl TuduArg {
@@ -153,6 +746,34 @@ impl TuduArg {
         self.get_str("name").expect("Should have name")
     }
 
+    /// The name of the `no_` flag that turns this boolean arg back off, if it has one
+    pub fn negation(&self) -> Option<&'static str> {
+        self.get_str("negation")
+    }
+
+    /// Builds the `no_` flag that explicitly turns this boolean arg off, if it has one
+    pub fn into_negation_arg(&self, include_long: bool) -> Option<Arg> {
+        let name = self.get_str("name").expect("Should have name");
+        let negation = self.negation()?;
+        let arg = Arg::new(negation)
+            .help(format!("Explicitly turn off --{}", name))
+            .action(ArgAction::SetTrue)
+            .conflicts_with(name);
+        Some(if include_long {
+            arg.long(negation)
+        } else {
+            arg
+        })
+    }
+
+    /// Builds the arg along with its `no_` flag, if it has one
+    pub fn into_negatable_args(self, include_long: bool) -> Vec<Arg> {
+        let negation = self.into_negation_arg(include_long);
+        let mut args = vec![self.into_arg(include_long)];
+        args.extend(negation);
+        args
+    }
+
     pub fn into_arg(self, include_long: bool) -> Arg {
         let name = self.get_str("name").expect("Should have name");
         let about = self.get_str("about").expect("Should have about");
@@ -160,7 +781,7 @@ impl TuduArg {
             TuduArg::Prompt => Arg::new(name)
                 .help(about)
                 .value_parser(NonEmptyStringValueParser::new()),
-            TuduArg::Name => Arg::new(name)
+            TuduArg::Name | TuduArg::ProjectName => Arg::new(name)
                 .help(about)
                 .value_parser(NonEmptyStringValueParser::new()),
             TuduArg::Description => Arg::new(name)
@@ -183,7 +804,7 @@ impl TuduArg {
             TuduArg::DueDate => Arg::new(name)
                 .help(about)
                 .value_parser(value_parser!(ValidDateTime)),
-            TuduArg::EstimatedMinutes => {
+            TuduArg::EstimatedMinutes | TuduArg::ActualMinutes => {
                 Arg::new(name).help(about).value_parser(value_parser!(i32))
             }
             TuduArg::Location => Arg::new(name)
@@ -197,20 +818,462 @@ impl TuduArg {
                 .default_value("to-do")
                 .value_parser(value_parser!(TodoStatus)),
             TuduArg::IncludeDone => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::HasOverdue => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::NoOverdue => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Output => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(PathBuf)),
+            TuduArg::Assignee => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::Tags => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(ValidTags)),
+            TuduArg::Tag => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::AssignedToMe => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Key => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::Value => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::Global => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::ShowIdOnly => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::NextColor => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::SinceCreated => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(i64).range(0..)),
+            TuduArg::OlderThan => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(i64).range(0..)),
+            TuduArg::CopyFrom => Arg::new(name).help(about).value_parser(value_parser!(i32)),
+            TuduArg::Format => Arg::new(name)
+                .help(about)
+                .default_value("normal")
+                .value_parser(value_parser!(OutputFormat)),
+            TuduArg::Limit => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(i64).range(1..)),
+            TuduArg::Offset => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(i64).range(0..)),
+            TuduArg::SortBy => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(SortField)),
+            TuduArg::Descending | TuduArg::IncludeArchived => {
+                Arg::new(name).help(about).action(ArgAction::SetTrue)
+            }
+            TuduArg::WithLastActivity => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithNote => Arg::new(name).help(about),
+            TuduArg::Reason => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::Week => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::StartDate => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(NaiveDate)),
+            TuduArg::Days => Arg::new(name)
+                .help(about)
+                .default_value("7")
+                .value_parser(value_parser!(u16).range(1..)),
+            TuduArg::ProjectStatus => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(ProjectStatus)),
+            TuduArg::BlockedBy => Arg::new(name).help(about).value_parser(value_parser!(i32)),
+            TuduArg::Yes => Arg::new(name)
+                .help(about)
+                .short('y')
+                .action(ArgAction::SetTrue),
+            TuduArg::OverdueFirst | TuduArg::Overdue => {
+                Arg::new(name).help(about).action(ArgAction::SetTrue)
+            }
+            TuduArg::Query => Arg::new(name)
+                .help(about)
+                .value_parser(NonEmptyStringValueParser::new()),
+            TuduArg::CaseSensitive => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::GroupByDay => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::StripMarkdown => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::NoStripMarkdown => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Cascade => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithProgress => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::SetDefault => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Check => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::AllowPastDue => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::ForceStatus => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::EstimatePerDay => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::InitConfig => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Force => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::Reopen => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::DebugDisplay => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithTodoCounts => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::CreatedAfter
+            | TuduArg::CreatedBefore
+            | TuduArg::UpdatedAfter
+            | TuduArg::UpdatedBefore
+            | TuduArg::DueBefore
+            | TuduArg::DueAfter => Arg::new(name)
+                .help(about)
+                .value_parser(value_parser!(ValidDateTime)),
+            TuduArg::WithUrl => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithoutUrl => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithDescription => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithoutDescription => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::ClearDescription => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithLocation => Arg::new(name).help(about).action(ArgAction::SetTrue),
+            TuduArg::WithoutLocation => Arg::new(name).help(about).action(ArgAction::SetTrue),
         };
         if include_long { arg.long(name) } else { arg }
     }
 }
 
-/// Helper function that parses arg matches and `.tudu` config for the required project_id
-pub fn parse_required_project_id(matches: &ArgMatches) -> TuduResult<i32> {
+/// Helper function that checks whether the arg was given on the command line rather than
+/// filled in from its default value
+pub fn is_arg_explicit(matches: &ArgMatches, arg: TuduArg) -> bool {
+    matches.value_source(arg.name()) == Some(ValueSource::CommandLine)
+}
+
+/// Helper function that reads a boolean arg and its `no_` flag, falling back to `default` when
+/// neither was given
+pub fn get_negatable_flag(matches: &ArgMatches, arg: TuduArg, default: bool) -> bool {
+    if matches.get_flag(arg.name()) {
+        return true;
+    }
+    match arg.negation() {
+        Some(negation) if matches.get_flag(negation) => false,
+        _ => default,
+    }
+}
+
+/// Helper function that parses arg matches and `.tudu` config for a project_id, along with where
+/// it came from
+pub fn parse_flag_or_config_project_id(
+    matches: &ArgMatches,
+) -> Option<(ProjectId, ProjectIdSource)> {
     let id: Option<&i32> = matches.get_one(TuduArg::ProjectId.name());
-    let project_id = match id {
-        Some(id_ref) => *id_ref,
-        None => match crate::config::get_project_id_from_config() {
-            Some(config_id) => config_id,
-            None => return Err(TuduError::RequiredArgumentError),
-        },
+    let (project_id, source) = match id {
+        Some(id_ref) => (*id_ref, ProjectIdSource::Flag),
+        None => {
+            let (config_id, path) = crate::config::get_project_id_with_path_from_config()?;
+            (config_id, ProjectIdSource::ConfigFile(path))
+        }
     };
-    Ok(project_id)
+    Some((ProjectId(project_id), source))
+}
+
+/// Helper function that parses arg matches and `.tudu` config for the required project_id,
+/// along with where it came from. Without either, the only active project is used.
+pub fn parse_required_project_id(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<(ProjectId, ProjectIdSource)> {
+    match parse_flag_or_config_project_id(matches) {
+        Some(found) => Ok(found),
+        None => auto_select_project_id(conn)?.ok_or(TuduError::RequiredArgumentError),
+    }
+}
+
+// The id of the only active project, or None when there are none or several to choose from
+fn auto_select_project_id(
+    conn: &mut SqliteConnection,
+) -> TuduResult<Option<(ProjectId, ProjectIdSource)>> {
+    let projects: Vec<(i32, String)> = ProjectQuery::new()
+        .build()
+        .select((projects_dsl::id, projects_dsl::name))
+        .limit(2)
+        .load(conn)?;
+    Ok(match projects.as_slice() {
+        [(id, name)] => Some((ProjectId(*id), ProjectIdSource::AutoSelected(name.clone()))),
+        _ => None,
+    })
+}
+
+/// Helper function that checks for markdown commonly pasted along with issue titles
+pub fn looks_like_markdown(s: &str) -> bool {
+    let markdown_regex = regex::Regex::new(r"\*\*|__|~~|`|\]\(|#\d").unwrap();
+    markdown_regex.is_match(s)
+}
+
+/// Helper function that removes markdown formatting and `#123` issue references from a string
+pub fn strip_markdown(s: &str) -> String {
+    let replacements = [
+        // [text](url) -> text
+        (r"\[([^\]]*)\]\([^)]*\)", "$1"),
+        (r"\*\*|__|~~|`", ""),
+        (r"\*", ""),
+        // _emphasis_, but not the underscores inside snake_case words
+        (r"(^|\W)_([^_]+)_(\W|$)", "${1}${2}${3}"),
+        (r"\(?#\d+\)?", ""),
+        (r"\s+", " "),
+    ];
+
+    let stripped = replacements
+        .iter()
+        .fold(s.to_string(), |acc, (pattern, replacement)| {
+            let regex = regex::Regex::new(pattern).unwrap();
+            regex.replace_all(&acc, *replacement).into_owned()
+        });
+    stripped.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Command;
+
+    // Wednesday, March 13th 2024
+    fn shorthand_now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 13)
+            .unwrap()
+            .and_hms_opt(10, 30, 0)
+            .unwrap()
+    }
+
+    fn end_of(year: i32, month: u32, day: u32) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(23, 59, 59)
+    }
+
+    #[test]
+    fn test_date_shorthand_eod() {
+        assert_eq!(
+            parse_date_shorthand("eod", shorthand_now()),
+            end_of(2024, 3, 13)
+        );
+    }
+
+    #[test]
+    fn test_date_shorthand_eow() {
+        assert_eq!(
+            parse_date_shorthand("EOW", shorthand_now()),
+            end_of(2024, 3, 15)
+        );
+        let saturday = end_of(2024, 3, 16).unwrap();
+        assert_eq!(parse_date_shorthand("eow", saturday), end_of(2024, 3, 22));
+    }
+
+    #[test]
+    fn test_date_shorthand_eom() {
+        assert_eq!(
+            parse_date_shorthand("eom", shorthand_now()),
+            end_of(2024, 3, 31)
+        );
+        let february = end_of(2024, 2, 10).unwrap();
+        assert_eq!(parse_date_shorthand("eom", february), end_of(2024, 2, 29));
+        let december = end_of(2024, 12, 5).unwrap();
+        assert_eq!(parse_date_shorthand("eom", december), end_of(2024, 12, 31));
+    }
+
+    #[test]
+    fn test_date_shorthand_eoy() {
+        assert_eq!(
+            parse_date_shorthand("eoy", shorthand_now()),
+            end_of(2024, 12, 31)
+        );
+    }
+
+    #[test]
+    fn test_date_shorthand_weekdays() {
+        let expected = [
+            ("monday", 18),
+            ("tuesday", 19),
+            ("wednesday", 20),
+            ("thursday", 14),
+            ("friday", 15),
+            ("saturday", 16),
+            ("sunday", 17),
+        ];
+        for (name, day) in expected {
+            assert_eq!(
+                parse_date_shorthand(name, shorthand_now()),
+                end_of(2024, 3, day),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_datetime_days() {
+        let now = shorthand_now();
+        assert_eq!(parse_relative_datetime("today", now), end_of(2024, 3, 13));
+        assert_eq!(
+            parse_relative_datetime("Tomorrow", now),
+            end_of(2024, 3, 14)
+        );
+        assert_eq!(
+            parse_relative_datetime("yesterday", now),
+            end_of(2024, 3, 12)
+        );
+        assert_eq!(parse_relative_datetime("now", now), Some(now));
+    }
+
+    #[test]
+    fn test_relative_datetime_offsets() {
+        let now = shorthand_now();
+        assert_eq!(
+            parse_relative_datetime("+2h", now),
+            Some(now + TimeDelta::hours(2))
+        );
+        assert_eq!(
+            parse_relative_datetime("+3d", now),
+            Some(now + TimeDelta::days(3))
+        );
+        assert_eq!(
+            parse_relative_datetime("+1w", now),
+            Some(now + TimeDelta::weeks(1))
+        );
+        assert_eq!(
+            parse_relative_datetime("next week", now),
+            Some(now + TimeDelta::weeks(1))
+        );
+        assert_eq!(parse_relative_datetime("+2m", now), None);
+        assert_eq!(parse_relative_datetime("+-2d", now), None);
+        assert_eq!(parse_relative_datetime("+é", now), None);
+    }
+
+    #[test]
+    fn test_valid_datetime_rejects_mistyped_relative_dates() {
+        assert!("tomorow".parse::<ValidDateTime>().is_err());
+        assert!("+2x".parse::<ValidDateTime>().is_err());
+        assert!("tomorrow".parse::<ValidDateTime>().is_ok());
+    }
+
+    #[test]
+    fn test_date_shorthand_falls_through_to_formats() {
+        assert_eq!(
+            parse_date_shorthand("2024-03-20 09:00", shorthand_now()),
+            None
+        );
+        let parsed: ValidDateTime = "2024-03-20 09:00".parse().unwrap();
+        assert_eq!(
+            parsed.0,
+            NaiveDate::from_ymd_opt(2024, 3, 20)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_valid_tags() {
+        let tags: ValidTags = " work, urgent,,work ".parse().unwrap();
+        assert_eq!(tags.to_string(), "work,urgent");
+        assert!(" , ".parse::<ValidTags>().is_err());
+    }
+
+    #[test]
+    fn test_valid_url_absolute() {
+        let url: ValidUrl = "https://example.com/issues/1".parse().unwrap();
+        assert!(matches!(url, ValidUrl::Absolute(_)));
+        assert_eq!(url.to_string(), "https://example.com/issues/1");
+    }
+
+    #[test]
+    fn test_valid_url_relative() {
+        for reference in ["/issues/123", "PROJ-456"] {
+            let url: ValidUrl = reference.parse().unwrap();
+            assert_eq!(url, ValidUrl::Relative(reference.to_string()));
+            assert_eq!(url.to_string(), reference);
+        }
+    }
+
+    #[test]
+    fn test_valid_url_rejects_spaces() {
+        assert!("not a url".parse::<ValidUrl>().is_err());
+    }
+
+    fn negatable_command() -> Command {
+        Command::new("test").args(TuduArg::IncludeDone.into_negatable_args(true))
+    }
+
+    #[test]
+    fn test_get_negatable_flag() {
+        let matches = negatable_command().get_matches_from(["test", "--include_done"]);
+        assert!(get_negatable_flag(&matches, TuduArg::IncludeDone, false));
+
+        let matches = negatable_command().get_matches_from(["test", "--no_include_done"]);
+        assert!(!get_negatable_flag(&matches, TuduArg::IncludeDone, true));
+
+        let matches = negatable_command().get_matches_from(["test"]);
+        assert!(get_negatable_flag(&matches, TuduArg::IncludeDone, true));
+    }
+
+    #[test]
+    fn test_negation_conflicts_with_flag() {
+        let result = negatable_command().try_get_matches_from([
+            "test",
+            "--include_done",
+            "--no_include_done",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_strip_markdown_bold_and_issue() {
+        assert_eq!(
+            strip_markdown("**[BUG] Fix login** (#123)"),
+            "[BUG] Fix login"
+        );
+    }
+
+    #[test]
+    fn test_strip_markdown_emphasis() {
+        assert_eq!(strip_markdown("*Fix* the __login__"), "Fix the login");
+        assert_eq!(strip_markdown("_Fix_ the login"), "Fix the login");
+    }
+
+    #[test]
+    fn test_strip_markdown_keeps_snake_case() {
+        assert_eq!(strip_markdown("Rename user_id"), "Rename user_id");
+    }
+
+    #[test]
+    fn test_strip_markdown_strikethrough_and_code() {
+        assert_eq!(strip_markdown("~~Old~~ Call `run()`"), "Old Call run()");
+    }
+
+    #[test]
+    fn test_strip_markdown_links() {
+        assert_eq!(
+            strip_markdown("See [the docs](https://example.com) first"),
+            "See the docs first"
+        );
+    }
+
+    #[test]
+    fn test_strip_markdown_issue_references() {
+        assert_eq!(strip_markdown("Fix crash #42"), "Fix crash");
+        assert_eq!(strip_markdown("Fix crash (#42) now"), "Fix crash now");
+    }
+
+    #[test]
+    fn test_looks_like_markdown() {
+        assert!(looks_like_markdown("**Bold** title"));
+        assert!(looks_like_markdown("Fix [link](url)"));
+        assert!(looks_like_markdown("Fix #12"));
+        assert!(!looks_like_markdown("Plain title"));
+        assert!(!looks_like_markdown("Rename user_id"));
+    }
+
+    #[test]
+    fn test_auto_select_project_id() {
+        use crate::infrastructure::database::{in_memory_connection, insert_test_project};
+
+        let mut conn = in_memory_connection();
+
+        let only = insert_test_project(&mut conn, "Only");
+        assert_eq!(
+            auto_select_project_id(&mut conn).unwrap(),
+            Some((
+                ProjectId(only.id),
+                ProjectIdSource::AutoSelected("Only".to_string())
+            ))
+        );
+
+        insert_test_project(&mut conn, "Second");
+        assert_eq!(auto_select_project_id(&mut conn).unwrap(), None);
+    }
 }
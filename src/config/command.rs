@@ -0,0 +1,110 @@
+use std::{env, path::PathBuf};
+
+use clap::{ArgGroup, ArgMatches, Command};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection, insert_into};
+
+use crate::{
+    arg::TuduArg,
+    config::{global_config_path, write_config_value, write_project_id_to_config},
+    display::{Display, Prefix, config_set_message},
+    error::{TuduError, TuduResult},
+    project::{
+        query::ProjectQuery,
+        sql::{NewProject, Project},
+    },
+    schema::projects::dsl as projects_dsl,
+};
+
+pub fn set_config_command() -> Command {
+    Command::new("set").args([
+        TuduArg::Key.into_arg(false).required(true),
+        TuduArg::Value.into_arg(false).required(true),
+        TuduArg::Global.into_arg(true),
+    ])
+}
+
+struct SetConfig {
+    key: String,
+    value: String,
+    path: PathBuf,
+}
+
+fn parse_set_config_command_matches(matches: &ArgMatches) -> TuduResult<SetConfig> {
+    let key: &String = matches
+        .get_one(TuduArg::Key.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    let value: &String = matches
+        .get_one(TuduArg::Value.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    let path = if matches.get_flag(TuduArg::Global.name()) {
+        global_config_path()
+    } else {
+        env::current_dir()?.join(".tudu")
+    };
+
+    Ok(SetConfig {
+        key: key.to_uppercase(),
+        value: value.to_owned(),
+        path,
+    })
+}
+
+pub fn handle_set_config_command(matches: &ArgMatches) -> TuduResult<()> {
+    let set_config = parse_set_config_command_matches(matches)?;
+    write_config_value(&set_config.path, &set_config.key, &set_config.value)?;
+    config_set_message(&set_config.key, &set_config.value, &set_config.path);
+    Ok(())
+}
+
+pub fn init_command() -> Command {
+    Command::new("init")
+        .about("Point the `.tudu` in the current directory at a project, creating it if needed")
+        .args([
+            TuduArg::ProjectId.into_arg(true),
+            TuduArg::ProjectName.into_arg(true),
+        ])
+        .group(
+            ArgGroup::new("project")
+                .args([TuduArg::ProjectId.name(), TuduArg::ProjectName.name()])
+                .required(true),
+        )
+}
+
+/// Writes `PROJECT_ID` to ./.tudu for an existing project, or for a new one named with
+/// `--project_name`.
+pub fn handle_init_command(matches: &ArgMatches, conn: &mut SqliteConnection) -> TuduResult<()> {
+    let project_id = matches.get_one::<i32>(TuduArg::ProjectId.name()).copied();
+    let project_name = matches.get_one::<String>(TuduArg::ProjectName.name());
+
+    let project_id = match (project_id, project_name) {
+        // Fails with NotFound instead of pointing the config at a project that doesn't exist
+        (Some(project_id), _) => ProjectQuery::new()
+            .include_inactive()
+            .build()
+            .filter(projects_dsl::id.eq(project_id))
+            .select(projects_dsl::id)
+            .first::<i32>(conn)?,
+        (None, Some(name)) => {
+            let project = conn.transaction(|conn| {
+                insert_into(projects_dsl::projects)
+                    .values(NewProject {
+                        name: name.clone(),
+                        description: None,
+                        color: None,
+                    })
+                    .get_result::<Project>(conn)
+            })?;
+            project.to_message(Some(Prefix::New)).display();
+            project.id
+        }
+        (None, None) => return Err(TuduError::RequiredArgumentError),
+    };
+
+    write_project_id_to_config(project_id)?;
+    config_set_message(
+        "PROJECT_ID",
+        &project_id.to_string(),
+        &env::current_dir()?.join(".tudu"),
+    );
+    Ok(())
+}
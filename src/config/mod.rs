@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+
+use crate::{
+    error::TuduResult,
+    infrastructure::env::{is_config_ignored, is_test_environment, tudu_home_dir},
+};
+
+pub mod command;
+
+/// Searches for the .tudu file starting from the given directory and moving up.
+///
+/// A .tudu file is skipped when its directory matches one of `ignore_patterns`.
+///
+/// # Returns
+///
+/// Returns `Some(PathBuf)` with the path to the .tudu file if found, otherwise `None`.
+fn find_tudu_file_from(start_dir: PathBuf, ignore_patterns: &[Pattern]) -> Option<PathBuf> {
+    let mut current_dir = start_dir;
+    loop {
+        let config_path = current_dir.join(".tudu");
+        if config_path.exists()
+            && let Ok(config_path) = fs::canonicalize(config_path)
+        {
+            let ignored = config_path.parent().is_some_and(|dir| {
+                ignore_patterns
+                    .iter()
+                    .any(|pattern| pattern.matches_path(dir))
+            });
+            if !ignored {
+                return Some(config_path);
+            }
+        }
+
+        if !current_dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// Gets the path of the global config file.
+///
+/// This is `$XDG_CONFIG_HOME/tudu/config`, defaulting to `.config/tudu/config` in the
+/// home directory.
+pub fn global_config_path() -> PathBuf {
+    let config_home = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => tudu_home_dir().join(".config"),
+    };
+    config_home.join("tudu").join("config")
+}
+
+// Parses the comma-separated glob patterns of `IGNORE_PATTERNS`, expanding a leading `~`.
+// Patterns that aren't valid globs are skipped.
+fn parse_ignore_patterns(value: &str) -> Vec<Pattern> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .filter_map(|pattern| {
+            let pattern = match pattern.strip_prefix('~') {
+                Some(rest) => format!("{}{}", tudu_home_dir().display(), rest),
+                None => pattern.to_string(),
+            };
+            Pattern::new(&pattern).ok()
+        })
+        .collect()
+}
+
+/// Parses a config file made of `KEY=VALUE` lines.
+///
+/// Blank lines, comment lines starting with `#` and lines without a `=` are skipped.
+/// Keys and values are trimmed, and values wrapped in double quotes are unquoted.
+///
+/// # Returns
+///
+/// Returns a `TuduError::IoError` if the file can't be read.
+pub fn parse_tudu_file(path: PathBuf) -> TuduResult<HashMap<String, String>> {
+    let contents = fs::read_to_string(path)?;
+    let mut values = HashMap::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        values.insert(key.to_string(), value.to_string());
+    }
+
+    Ok(values)
+}
+
+/// Reads the value for `key` from a config file made of `KEY=VALUE` lines.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the trimmed value if the file has the key, otherwise `None`.
+fn read_config_value(config_path: &Path, key: &str) -> Option<String> {
+    parse_tudu_file(config_path.to_path_buf()).ok()?.remove(key)
+}
+
+/// Writes `KEY=VALUE` to the config file, replacing any existing line for the key.
+///
+/// The file and its parent directories are created if they don't exist.
+pub fn write_config_value(config_path: &Path, key: &str, value: &str) -> TuduResult<()> {
+    let prefix = format!("{}=", key);
+    let new_line = format!("{}{}", prefix, value);
+
+    let mut lines: Vec<String> = match fs::read_to_string(config_path) {
+        Ok(contents) => contents.lines().map(str::to_string).collect(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => return Err(err.into()),
+    };
+
+    match lines
+        .iter_mut()
+        .find(|line| line.starts_with(prefix.as_str()))
+    {
+        Some(line) => *line = new_line,
+        None => lines.push(new_line),
+    }
+
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(config_path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// Writes `PROJECT_ID` to the `.tudu` in the current directory, replacing any existing value.
+pub fn write_project_id_to_config(project_id: i32) -> TuduResult<()> {
+    write_project_id_to_config_in(&env::current_dir()?, project_id)
+}
+
+// Writes `PROJECT_ID` to the .tudu in `dir`, so tests don't need to change the working directory
+fn write_project_id_to_config_in(dir: &Path, project_id: i32) -> TuduResult<()> {
+    write_config_value(&dir.join(".tudu"), "PROJECT_ID", &project_id.to_string())
+}
+
+// Looks the key up in the local .tudu found from `start_dir` first, then in the global config
+fn get_config_value_from(
+    start_dir: PathBuf,
+    global_path: Option<PathBuf>,
+    key: &str,
+) -> Option<String> {
+    get_config_value_with_path_from(start_dir, global_path, key).map(|(value, _)| value)
+}
+
+// Like `get_config_value_from`, but also returns the path of the file the value was read from
+fn get_config_value_with_path_from(
+    start_dir: PathBuf,
+    global_path: Option<PathBuf>,
+    key: &str,
+) -> Option<(String, PathBuf)> {
+    let ignore_patterns = global_path
+        .as_deref()
+        .and_then(|path| read_config_value(path, "IGNORE_PATTERNS"))
+        .map(|value| parse_ignore_patterns(&value))
+        .unwrap_or_default();
+    if let Some(local_path) = find_tudu_file_from(start_dir, &ignore_patterns) {
+        if is_test_environment() {
+            println!("{}", local_path.display());
+        }
+        if let Some(value) = read_config_value(&local_path, key) {
+            return Some((value, local_path));
+        }
+    }
+
+    let global_path = global_path?;
+    let value = read_config_value(&global_path, key)?;
+    Some((value, global_path))
+}
+
+/// Reads a config value, checking the nearest .tudu file before the global config.
+///
+/// # Returns
+///
+/// Returns `Some(String)` with the value if either config has the key, otherwise `None`.
+pub fn get_config_value(key: &str) -> Option<String> {
+    let current_dir = env::current_dir().ok()?;
+    get_config_value_from(current_dir, Some(global_config_path()), key)
+}
+
+/// Reads a config value from the global config only.
+pub fn get_global_config_value(key: &str) -> Option<String> {
+    read_config_value(&global_config_path(), key)
+}
+
+/// Reads the project ID from the .tudu file by searching parent directories,
+/// falling back to the global config.
+///
+/// The config file is expected to contain a line like:
+/// PROJECT_ID=123
+///
+/// # Returns
+///
+/// Returns `Some(i32)` with the project ID if found and valid, otherwise `None`.
+pub fn get_project_id_from_config() -> Option<i32> {
+    get_project_id_with_path_from_config().map(|(id, _)| id)
+}
+
+/// Reads the project ID like `get_project_id_from_config`, along with the path of the
+/// config file it was read from.
+///
+/// Returns `None` without looking at any config when `TUDU_IGNORE_CONFIG=1` is set.
+pub fn get_project_id_with_path_from_config() -> Option<(i32, PathBuf)> {
+    if is_config_ignored() {
+        return None;
+    }
+    let current_dir = env::current_dir().ok()?;
+    let (value, path) =
+        get_config_value_with_path_from(current_dir, Some(global_config_path()), "PROJECT_ID")?;
+    Some((value.parse::<i32>().ok()?, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn run_test_in_temp_dir<F>(test_fn: F)
+    where
+        F: FnOnce(&TempDir),
+    {
+        let dir = tempfile::tempdir().unwrap();
+        test_fn(&dir);
+    }
+
+    #[test]
+    fn test_find_in_current_dir() {
+        run_test_in_temp_dir(|dir| {
+            let tudu_path = dir.path().join(".tudu");
+            File::create(&tudu_path).unwrap();
+            let found_path = find_tudu_file_from(dir.path().to_path_buf(), &[]).unwrap();
+            assert_eq!(found_path, fs::canonicalize(tudu_path).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_find_in_parent_dir() {
+        run_test_in_temp_dir(|parent_dir| {
+            let child_dir = parent_dir.path().join("child");
+            fs::create_dir(&child_dir).unwrap();
+
+            let tudu_path = parent_dir.path().join(".tudu");
+            File::create(&tudu_path).unwrap();
+
+            let found_path = find_tudu_file_from(child_dir, &[]).unwrap();
+            assert_eq!(found_path, fs::canonicalize(tudu_path).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_find_skips_ignored_dirs() {
+        run_test_in_temp_dir(|parent_dir| {
+            let child_dir = parent_dir.path().join("scratch");
+            fs::create_dir(&child_dir).unwrap();
+            File::create(child_dir.join(".tudu")).unwrap();
+            let parent_tudu = parent_dir.path().join(".tudu");
+            File::create(&parent_tudu).unwrap();
+
+            let patterns = parse_ignore_patterns(" */scratch, [invalid ,");
+            assert_eq!(patterns.len(), 1);
+
+            let found_path = find_tudu_file_from(child_dir, &patterns).unwrap();
+            assert_eq!(found_path, fs::canonicalize(parent_tudu).unwrap());
+        });
+    }
+
+    #[test]
+    fn test_not_found() {
+        run_test_in_temp_dir(|dir| {
+            assert_eq!(find_tudu_file_from(dir.path().to_path_buf(), &[]), None);
+        });
+    }
+
+    #[test]
+    fn test_get_project_id_from_config_found() {
+        run_test_in_temp_dir(|dir| {
+            let original_dir = env::current_dir().unwrap();
+            env::set_current_dir(dir.path()).unwrap();
+
+            let tudu_path = dir.path().join(".tudu");
+            let mut file = File::create(&tudu_path).unwrap();
+            writeln!(file, "PROJECT_ID=42").unwrap();
+
+            assert_eq!(get_project_id_from_config(), Some(42));
+
+            env::set_current_dir(original_dir).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_read_config_value() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join("config");
+            fs::write(&config_path, "OTHER=1\nPROJECT_ID= 7 \n").unwrap();
+
+            assert_eq!(
+                read_config_value(&config_path, "PROJECT_ID"),
+                Some("7".to_string())
+            );
+            assert_eq!(read_config_value(&config_path, "MISSING"), None);
+        });
+    }
+
+    #[test]
+    fn test_parse_tudu_file_skips_comments_and_blank_lines() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join(".tudu");
+            fs::write(
+                &config_path,
+                "# The project\n\nPROJECT_ID=3\n  # indented\n",
+            )
+            .unwrap();
+
+            let values = parse_tudu_file(config_path).unwrap();
+            assert_eq!(values.len(), 1);
+            assert_eq!(values.get("PROJECT_ID"), Some(&"3".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_parse_tudu_file_quoted_values() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join(".tudu");
+            fs::write(&config_path, "NAME=\"value with spaces\"\nHALF=\"open\n").unwrap();
+
+            let values = parse_tudu_file(config_path).unwrap();
+            assert_eq!(values.get("NAME"), Some(&"value with spaces".to_string()));
+            assert_eq!(values.get("HALF"), Some(&"\"open".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_parse_tudu_file_malformed_lines() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join(".tudu");
+            fs::write(&config_path, "not a pair\n=no key\nKEY=a=b\n").unwrap();
+
+            let values = parse_tudu_file(config_path).unwrap();
+            assert_eq!(values.len(), 1);
+            assert_eq!(values.get("KEY"), Some(&"a=b".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_parse_tudu_file_missing() {
+        run_test_in_temp_dir(|dir| {
+            let result = parse_tudu_file(dir.path().join("missing"));
+            assert!(matches!(result, Err(crate::error::TuduError::IoError(_))));
+        });
+    }
+
+    #[test]
+    fn test_get_config_value_prefers_local() {
+        run_test_in_temp_dir(|dir| {
+            let global_path = dir.path().join("global").join("config");
+            write_config_value(&global_path, "PROJECT_ID", "1").unwrap();
+            write_config_value(&global_path, "DATABASE_URL", "/tmp/tudu.db").unwrap();
+
+            let project_dir = dir.path().join("project");
+            fs::create_dir(&project_dir).unwrap();
+            fs::write(project_dir.join(".tudu"), "PROJECT_ID=2\n").unwrap();
+
+            let value =
+                get_config_value_from(project_dir.clone(), Some(global_path.clone()), "PROJECT_ID");
+            assert_eq!(value, Some("2".to_string()));
+
+            let value = get_config_value_from(project_dir, Some(global_path), "DATABASE_URL");
+            assert_eq!(value, Some("/tmp/tudu.db".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_get_config_value_falls_back_to_global() {
+        run_test_in_temp_dir(|dir| {
+            let global_path = dir.path().join("global").join("config");
+            write_config_value(&global_path, "PROJECT_ID", "1").unwrap();
+
+            let value =
+                get_config_value_from(dir.path().to_path_buf(), Some(global_path), "PROJECT_ID");
+            assert_eq!(value, Some("1".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_get_config_value_with_path_reports_source() {
+        run_test_in_temp_dir(|dir| {
+            let global_path = dir.path().join("global").join("config");
+            write_config_value(&global_path, "DATABASE_URL", "/tmp/tudu.db").unwrap();
+
+            let project_dir = dir.path().join("project");
+            fs::create_dir(&project_dir).unwrap();
+            fs::write(project_dir.join(".tudu"), "PROJECT_ID=2\n").unwrap();
+            let local_path = fs::canonicalize(project_dir.join(".tudu")).unwrap();
+
+            let value = get_config_value_with_path_from(
+                project_dir.clone(),
+                Some(global_path.clone()),
+                "PROJECT_ID",
+            );
+            assert_eq!(value, Some(("2".to_string(), local_path)));
+
+            let value = get_config_value_with_path_from(
+                project_dir,
+                Some(global_path.clone()),
+                "DATABASE_URL",
+            );
+            assert_eq!(value, Some(("/tmp/tudu.db".to_string(), global_path)));
+        });
+    }
+
+    #[test]
+    fn test_write_config_value_replaces_existing_key() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join(".tudu");
+            fs::write(&config_path, "PROJECT_ID=1\nOTHER=x\n").unwrap();
+
+            write_config_value(&config_path, "PROJECT_ID", "5").unwrap();
+            write_config_value(&config_path, "NEW", "y").unwrap();
+
+            assert_eq!(
+                fs::read_to_string(&config_path).unwrap(),
+                "PROJECT_ID=5\nOTHER=x\nNEW=y\n"
+            );
+        });
+    }
+
+    #[test]
+    fn test_write_project_id_to_config() {
+        run_test_in_temp_dir(|dir| {
+            let config_path = dir.path().join(".tudu");
+
+            write_project_id_to_config_in(dir.path(), 3).unwrap();
+            assert_eq!(fs::read_to_string(&config_path).unwrap(), "PROJECT_ID=3\n");
+
+            fs::write(&config_path, "OTHER=x\nPROJECT_ID=3\n").unwrap();
+            write_project_id_to_config_in(dir.path(), 42).unwrap();
+            assert_eq!(
+                fs::read_to_string(&config_path).unwrap(),
+                "OTHER=x\nPROJECT_ID=42\n"
+            );
+        });
+    }
+}
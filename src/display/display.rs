@@ -1,11 +1,19 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
 
 use crate::{
+    arg::ValidUrl,
     display::{
         message::{Message, Prefix},
+        progress::ProgressBar,
         text::Text,
     },
-    project::sql::Project,
+    infrastructure::env,
+    project::{
+        sql::{Project, todo_summary_line},
+        summary::ProjectSummary,
+    },
+    stats::ProjectStats,
     todo::{
         group::TodoGroup,
         sql::{Todo, TodoPriority, TodoStatus},
@@ -13,92 +21,557 @@ use crate::{
 };
 
 const DATETIME_FORMAT: &str = "%a %b %-d, %Y %-I:%M%P";
+const DATETIME_FORMAT_24H: &str = "%a %b %-d, %Y %H:%M";
+
+/// Formats a datetime with the 12 or 24-hour clock, depending on the user's preference.
+pub fn display_datetime(dt: NaiveDateTime) -> String {
+    format_datetime_clock(dt, env::is_24_hour_clock())
+}
+
+fn format_datetime_clock(dt: NaiveDateTime, use_24_hour_clock: bool) -> String {
+    let format = if use_24_hour_clock {
+        DATETIME_FORMAT_24H
+    } else {
+        DATETIME_FORMAT
+    };
+    dt.format(format).to_string()
+}
+
+// Wide enough for the longest label, `Completed:`, so the dates line up
+const LABEL_WIDTH: usize = 10;
 
+// Formats a labelled datetime with the label padded to `LABEL_WIDTH`, e.g. `Created:   <date>`
 fn format_datetime(prefix: &str, naive_date_time: NaiveDateTime) -> String {
-    format!("{}: {}", prefix, naive_date_time.format(DATETIME_FORMAT))
+    let label = format!("{}:", prefix);
+    format!(
+        "{:<width$} {}",
+        label,
+        display_datetime(naive_date_time),
+        width = LABEL_WIDTH
+    )
+}
+
+// Formats how long before `now` the given time was, e.g. "2 hours ago"
+fn format_datetime_relative(naive_date_time: NaiveDateTime, now: NaiveDateTime) -> String {
+    let elapsed = now - naive_date_time;
+    let (value, unit) = if elapsed.num_days() > 0 {
+        (elapsed.num_days(), "day")
+    } else if elapsed.num_hours() > 0 {
+        (elapsed.num_hours(), "hour")
+    } else if elapsed.num_minutes() > 0 {
+        (elapsed.num_minutes(), "minute")
+    } else {
+        return "just now".to_string();
+    };
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{} {}{} ago", value, unit, plural)
 }
 
 pub trait Display {
     fn to_message(&self, prefix: Option<Prefix>) -> Message;
     fn to_detailed_message(&self, prefix: Option<Prefix>) -> Message;
+
+    /// The raw field values from the `Debug` output, without any styling, for troubleshooting.
+    fn to_debug_message(&self) -> Message
+    where
+        Self: std::fmt::Debug,
+    {
+        format!("{:#?}", self)
+            .lines()
+            .fold(Message::new(), |message, line| {
+                message.add_line(Text::new(line.to_string()))
+            })
+    }
 }
 
 fn priority_text(priority: TodoPriority) -> Text {
-    let text = Text::new(format!("[P{}]", priority as i32));
-    match priority {
-        TodoPriority::Low => text.color("#198754".to_string()),
-        TodoPriority::Medium => text.color("#0DCAF0".to_string()),
-        TodoPriority::High => text.color("#FFC107".to_string()),
-        TodoPriority::Urgent => text.color("#DC3545".to_string()),
-    }
+    Text::new(format!("[P{}]", priority as i32)).color(priority.color_hex().to_string())
 }
 
 fn status_text(status: TodoStatus) -> Text {
-    let text = Text::new(format!("[{}] ", status)).padding_right(15);
-    match status {
-        TodoStatus::ToDo => text.color("#CED4DA".to_string()),
-        TodoStatus::InProgress => text.color("#0D6EFD".to_string()),
-        TodoStatus::Done => text.color("#198754".to_string()),
-        TodoStatus::Blocked => text.color("#DC3545".to_string()),
-        TodoStatus::OnHold => text.color("#FFC107".to_string()),
-        TodoStatus::Cancelled => text.color("#6C757D".to_string()),
+    Text::new(format!("[{}] ", status))
+        .padding_right(15)
+        .color(status.color_hex().to_string())
+}
+
+// The description of a blocked todo that explains what blocks it, shown inline in details
+fn inline_blocked_description(todo: &Todo) -> Option<&str> {
+    match &todo.description {
+        Some(desc) if todo.status == TodoStatus::Blocked && desc.starts_with("Blocked by:") => {
+            Some(desc.as_str())
+        }
+        _ => None,
+    }
+}
+
+const AGING_DAYS: i64 = 3;
+
+// A `[Nd]` badge colored by how long the todo has been around
+fn age_badge(age_in_days: i64, stale_days: i64) -> Text {
+    let badge = Text::new(format!("[{}d]", age_in_days));
+    if age_in_days > stale_days {
+        badge.error()
+    } else if age_in_days >= AGING_DAYS {
+        badge.warning()
+    } else {
+        badge.success()
+    }
+}
+
+// Titles longer than this are cut short in the compact todo line
+const TITLE_MAX_WIDTH: usize = 50;
+
+// The single summary line of a todo, optionally with text right after the status tag
+fn todo_line(
+    todo: &Todo,
+    after_status: Option<&str>,
+    show_age: bool,
+    max_title_width: Option<usize>,
+) -> String {
+    let id = Text::new(format!("#{}", todo.id)).padding_right(5);
+    let priority = priority_text(todo.priority);
+    let status = status_text(todo.status).padding_right(5);
+    let title = match max_title_width {
+        Some(width) => Text::new(todo.title.clone()).truncate(width),
+        None => Text::new(todo.title.clone()),
+    };
+    // Finished work takes on the done color so it stands apart from the open todos
+    let title = if todo.status == TodoStatus::Done {
+        title.color(todo.status.color_hex().to_string())
+    } else {
+        title
+    }
+    .padding_right(5);
+
+    let mut line = format!("{}{}{}", id, priority, status);
+
+    if let Some(text) = after_status {
+        let t = Text::new(text.to_string()).error();
+        line += format!("{} ", t).as_str();
+    }
+
+    line += title.to_string().as_str();
+
+    let tags = todo.tag_list();
+    if !tags.is_empty() {
+        let t = Text::new(format!("[{}]", tags.join(" ")))
+            .color("#17A2B8".to_string())
+            .padding_left(1);
+        line += t.to_string().as_str();
+    }
+
+    if let Some(assignee) = &todo.assignee {
+        let a = Text::new(format!("@{}", assignee))
+            .color("#6F42C1".to_string())
+            .padding_left(1)
+            .padding_right(5);
+        line += a.to_string().as_str();
+    }
+
+    if show_age && let Some(age) = todo.age_in_days() {
+        let badge = age_badge(age, env::stale_days_env());
+        line += format!(" {}", badge).as_str();
     }
+
+    if let Some(d_date) = todo.due_date {
+        let d = Text::new(format!("Due: {}", display_datetime(d_date)));
+        let d = if todo.is_overdue() { d.error() } else { d }.padding_left(5);
+        line += d.to_string().as_str();
+    }
+
+    line
 }
 
 impl Display for Todo {
     fn to_message(&self, prefix: Option<Prefix>) -> Message {
-        let id = Text::new(format!("#{}", self.id)).padding_right(5);
-        let priority = priority_text(self.priority);
-        let status = status_text(self.status).padding_right(5);
-        let title = Text::new(self.title.clone()).padding_right(5);
-
-        let mut line = format!("{}{}{}{}", id, priority, status, title);
+        let mut message = Message::new().add_line(Text::new(todo_line(
+            self,
+            None,
+            false,
+            Some(TITLE_MAX_WIDTH),
+        )));
 
-        if let Some(d_date) = self.due_date {
-            let d = Text::new(format_datetime("Due", d_date)).padding_left(5);
-            line += d.to_string().as_str();
+        if let Some(p) = prefix {
+            message = message.with_prefix(p);
         }
 
+        message
+    }
+
+    fn to_detailed_message(&self, prefix: Option<Prefix>) -> Message {
+        let line = todo_line(self, inline_blocked_description(self), false, None);
         let mut message = Message::new().add_line(Text::new(line));
 
         if let Some(p) = prefix {
             message = message.with_prefix(p);
         }
 
+        for line in create_additional_lines(self).into_iter() {
+            message = message.add_line(line);
+        }
         message
     }
+}
 
-    fn to_detailed_message(&self, prefix: Option<Prefix>) -> Message {
-        let mut simple_message = self.to_message(prefix);
-        let additional_lines = create_additional_lines(self);
+/// Builds the headers and rows of a table with one row per todo and every column the wide
+/// format shows.
+///
+/// Missing values are rendered as `-`.
+pub fn todo_table(todos: &[Todo]) -> (Vec<String>, Vec<Vec<String>>) {
+    let headers = [
+        "id", "priority", "status", "title", "due", "location", "estimate",
+    ];
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let rows = todos
+        .iter()
+        .map(|todo| {
+            vec![
+                format!("#{}", todo.id),
+                Text::new(format!("P{}", todo.priority as i32))
+                    .color(todo.priority.color_hex().to_string())
+                    .to_string(),
+                todo.status.to_string(),
+                todo.title.clone(),
+                or_dash(todo.due_date.map(display_datetime)),
+                or_dash(todo.location.clone()),
+                or_dash(todo.estimated_minutes.map(|m| format!("{}min", m))),
+            ]
+        })
+        .collect();
+
+    (headers.iter().map(|h| h.to_string()).collect(), rows)
+}
+
+// The project name in its own color, as shown in table cells
+fn project_name_cell(project: &Project) -> String {
+    Text::new(project.name.clone())
+        .color(project.display_color())
+        .bold()
+        .to_string()
+}
+
+/// Builds the headers and rows of the `tudu stats` table, one row per project.
+pub fn stats_table(stats: &[ProjectStats]) -> (Vec<String>, Vec<Vec<String>>) {
+    let headers = [
+        "id",
+        "project",
+        "total",
+        "open",
+        "in progress",
+        "done",
+        "cancelled",
+        "overdue",
+        "estimated",
+        "avg estimate",
+    ];
+    let rows = stats
+        .iter()
+        .map(|stats| {
+            let overdue = match stats.overdue {
+                0 => "0".to_string(),
+                count => Text::new(count.to_string()).error().to_string(),
+            };
+            vec![
+                format!("#{}", stats.project.id),
+                project_name_cell(&stats.project),
+                stats.total.to_string(),
+                stats.open.to_string(),
+                stats.in_progress.to_string(),
+                stats.done.to_string(),
+                stats.cancelled.to_string(),
+                overdue,
+                format!("{}min", stats.total_estimated_minutes),
+                stats
+                    .average_estimated_minutes
+                    .map(|minutes| format!("{:.0}min", minutes))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    (headers.iter().map(|h| h.to_string()).collect(), rows)
+}
+
+/// Builds the headers and rows of `list project --with_todo_counts`, one row per project.
+///
+/// The overdue, progress and last activity columns are only added when they were computed.
+pub fn project_counts_table(summaries: &[ProjectSummary]) -> (Vec<String>, Vec<Vec<String>>) {
+    let with_overdue = summaries.iter().any(|s| s.overdue_count.is_some());
+    let with_progress = summaries.iter().any(|s| s.progress.is_some());
+    let with_last_activity = summaries.iter().any(|s| s.last_activity.is_some());
+
+    let mut headers = vec!["id", "project", "todos"];
+    if with_overdue {
+        headers.push("overdue");
+    }
+    if with_progress {
+        headers.push("progress");
+    }
+    if with_last_activity {
+        headers.push("last activity");
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let rows = summaries
+        .iter()
+        .map(|summary| {
+            let todos = summary
+                .todo_counts
+                .as_ref()
+                .map(todo_summary_line)
+                .filter(|line| !line.is_empty())
+                .unwrap_or_else(|| "No todos".to_string());
+            let mut row = vec![
+                format!("#{}", summary.project.id),
+                project_name_cell(&summary.project),
+                todos,
+            ];
+            if with_overdue {
+                row.push(summary.overdue_count.unwrap_or(0).to_string());
+            }
+            if with_progress {
+                row.push(
+                    summary
+                        .progress
+                        .map(|progress| format!("{}/{} done", progress.done, progress.total))
+                        .unwrap_or_else(|| "-".to_string()),
+                );
+            }
+            if with_last_activity {
+                row.push(
+                    summary
+                        .last_activity
+                        .as_ref()
+                        .and_then(|activity| activity.last_updated_at)
+                        .map(|updated_at| format_datetime_relative(updated_at, now))
+                        .unwrap_or_else(|| "none".to_string()),
+                );
+            }
+            row
+        })
+        .collect();
+
+    (headers.iter().map(|h| h.to_string()).collect(), rows)
+}
 
-        for line in additional_lines.into_iter() {
-            simple_message = simple_message.add_line(line);
+// Counts the todos in each status, in the order the statuses are declared
+fn count_by_status(todos: &[Todo]) -> Vec<(TodoStatus, usize)> {
+    let counts = todos.iter().fold([0usize; 6], |mut counts, todo| {
+        counts[todo.status as usize] += 1;
+        counts
+    });
+    TodoStatus::value_variants()
+        .iter()
+        .map(|status| (*status, counts[*status as usize]))
+        .collect()
+}
+
+/// Builds a single line counting the todos in each status, e.g. `ToDo: 5 • Done: 8`.
+///
+/// Statuses without todos are left out and `None` is returned when there are no todos at all.
+pub fn todo_status_bar(todos: &[Todo]) -> Option<String> {
+    let segments: Vec<String> = count_by_status(todos)
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(status, count)| {
+            Text::new(format!("{}: {}", status, count))
+                .color(status.color_hex().to_string())
+                .to_string()
+        })
+        .collect();
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join(" • "))
+    }
+}
+
+/// Builds the bold `Average workload: ~2h 15min/day` summary, or `N/A` without an estimate.
+pub fn workload_message(minutes_per_day: Option<f64>) -> Message {
+    let workload = match minutes_per_day {
+        Some(minutes) => {
+            let minutes = minutes.round() as i64;
+            let duration = match (minutes / 60, minutes % 60) {
+                (0, m) => format!("{}min", m),
+                (h, 0) => format!("{}h", h),
+                (h, m) => format!("{}h {}min", h, m),
+            };
+            format!("~{}/day (assuming 5-day weeks)", duration)
+        }
+        None => "N/A".to_string(),
+    };
+    Message::new().add_line(Text::new(format!("Average workload: {}", workload)).bold())
+}
+
+const CALENDAR_DATE_FORMAT: &str = "%a %b %-d";
+const DAY_HEADING_FORMAT: &str = "%A, %b %-d";
+
+/// Labels a day relative to today: `Today`, `Tomorrow`, `Yesterday` or e.g. `Monday, Jun 2`.
+pub fn format_day_heading(date: NaiveDate) -> String {
+    format_day_heading_from(date, chrono::Local::now().date_naive())
+}
+
+fn format_day_heading_from(date: NaiveDate, today: NaiveDate) -> String {
+    match (date - today).num_days() {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        -1 => "Yesterday".to_string(),
+        _ => date.format(DAY_HEADING_FORMAT).to_string(),
+    }
+}
+
+/// Builds a calendar with a header row of dates followed by the todos due on each day.
+///
+/// Todos without a due date are listed in a trailing `Undated` section.
+pub fn todo_calendar(days: &[(NaiveDate, Vec<Todo>)], undated: &[Todo]) -> Message {
+    let dates: Vec<String> = days
+        .iter()
+        .map(|(date, _)| date.format(CALENDAR_DATE_FORMAT).to_string())
+        .collect();
+    let rule = dates
+        .iter()
+        .map(|date| "─".repeat(date.chars().count() + 2))
+        .collect::<Vec<String>>()
+        .join("┼");
+    let header = dates
+        .iter()
+        .map(|date| format!(" {} ", date))
+        .collect::<Vec<String>>()
+        .join("│");
+
+    let mut message = Message::new()
+        .add_line(Text::new(header).bold())
+        .add_line(Text::new(rule));
+
+    for (date, todos) in days {
+        message = message
+            .add_line(Text::new(String::new()))
+            .add_line(Text::new(date.format(CALENDAR_DATE_FORMAT).to_string()).bold());
+        if todos.is_empty() {
+            message = message.add_line(Text::new("(nothing due)".to_string()).dimmed());
+        }
+        for todo in todos {
+            for line in todo.to_message(None).lines {
+                message = message.add_line(line);
+            }
+        }
+    }
+
+    if !undated.is_empty() {
+        message = message
+            .add_line(Text::new(String::new()))
+            .add_line(Text::new("Undated".to_string()).bold());
+        for todo in undated {
+            for line in todo.to_message(None).lines {
+                message = message.add_line(line);
+            }
         }
-        simple_message
+    }
+
+    message
+}
+
+// How far a todo went over its estimate, which picks the color of the time comparison
+#[derive(Debug, PartialEq)]
+enum TimeVariance {
+    Within,
+    SlightlyOver,
+    Over,
+}
+
+// Up to this many percent over the estimate still counts as slightly over
+const SLIGHTLY_OVER_PERCENT: f64 = 25.0;
+
+fn time_variance(est: i32, actual: i32) -> TimeVariance {
+    if actual <= est {
+        TimeVariance::Within
+    } else if est > 0 && (actual - est) as f64 * 100.0 / est as f64 <= SLIGHTLY_OVER_PERCENT {
+        TimeVariance::SlightlyOver
+    } else {
+        TimeVariance::Over
+    }
+}
+
+/// Compares the estimated and actual minutes of a todo, e.g.
+/// `⏱ est: 30min | actual: 45min (50% over)`.
+///
+/// The comparison is green when the todo took at most its estimate, yellow when it went up
+/// to 25% over and red beyond that.
+pub fn format_time_comparison(est: i32, actual: i32) -> String {
+    let times = format!("⏱ est: {}min | actual: {}min", est, actual);
+    // Without an estimate there is no percentage to compare against
+    let line = if est <= 0 {
+        times
+    } else {
+        let percentage = ((actual - est).abs() as f64 * 100.0 / est as f64).round() as i64;
+        match actual.cmp(&est) {
+            std::cmp::Ordering::Less => format!("{} ({}% under)", times, percentage),
+            std::cmp::Ordering::Equal => format!("{} (on estimate)", times),
+            std::cmp::Ordering::Greater => format!("{} ({}% over)", times, percentage),
+        }
+    };
+
+    let text = Text::new(line);
+    match time_variance(est, actual) {
+        TimeVariance::Within => text.success(),
+        TimeVariance::SlightlyOver => text.warning(),
+        TimeVariance::Over => text.error(),
+    }
+    .to_string()
+}
+
+// Wraps `text` in an OSC 8 terminal hyperlink to `url` when colors are enabled
+fn hyperlink(url: &str, text: &str) -> String {
+    if colored::control::SHOULD_COLORIZE.should_colorize() {
+        format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+    } else {
+        text.to_string()
     }
 }
 
 fn create_additional_lines(todo: &Todo) -> Vec<Text> {
     let mut lines = Vec::new();
 
-    if let Some(desc) = &todo.description {
+    if let Some(desc) = &todo.description
+        && inline_blocked_description(todo).is_none()
+    {
         lines.push(Text::new(desc.clone()));
     }
 
     let mut detail_parts = Vec::new();
+    if let Some(assignee) = &todo.assignee {
+        detail_parts.push(format!("👤 @{}", assignee));
+    }
     if let Some(location) = &todo.location {
         detail_parts.push(format!("📍 {}", location));
     }
     if let Some(url) = &todo.url {
-        detail_parts.push(format!("🔗 {}", url));
+        let link = match url.parse::<ValidUrl>() {
+            Ok(ValidUrl::Absolute(absolute)) => hyperlink(absolute.as_str(), url),
+            _ => url.clone(),
+        };
+        detail_parts.push(format!("🔗 {}", link));
     }
-    if let Some(minutes) = todo.estimated_minutes {
-        detail_parts.push(format!("⏱️  {}min", minutes));
+    // A done todo with both times compares them on a line of its own below the other details
+    let mut time_comparison = None;
+    match (todo.estimated_minutes, todo.actual_minutes) {
+        (Some(est), Some(actual)) if todo.status == TodoStatus::Done => {
+            time_comparison = Some(format_time_comparison(est, actual));
+        }
+        (Some(est), Some(actual)) => {
+            detail_parts.push(format!("⏱️  {}min / {}min actual", est, actual))
+        }
+        (Some(est), None) => detail_parts.push(format!("⏱️  {}min", est)),
+        (None, Some(actual)) => detail_parts.push(format!("⏱️  {}min actual", actual)),
+        (None, None) => {}
     }
     if !detail_parts.is_empty() {
         lines.push(Text::new(detail_parts.join(" • ")));
     }
+    if let Some(comparison) = time_comparison {
+        lines.push(Text::new(comparison));
+    }
 
     let mut timestamp_parts = Vec::new();
     if let Some(created) = todo.created_at {
@@ -117,15 +590,61 @@ fn create_additional_lines(todo: &Todo) -> Vec<Text> {
     lines
 }
 
+// The `#id Name` heading line shared by project messages
+fn project_heading(project: &Project) -> String {
+    let id = Text::new(format!("#{}", project.id)).padding_right(5);
+    let heading = Text::new(project.name.clone())
+        .color(project.display_color())
+        .bold()
+        .padding_left(2);
+    let mut line = format!("{}{}", id, heading);
+    if !project.is_active() {
+        let status = Text::new(format!("[{}]", project.status))
+            .dimmed()
+            .padding_left(1);
+        line += status.to_string().as_str();
+    }
+    line
+}
+
+/// Builds the `#1 Project > #3 Parent > #5 Todo` line shown above a subtodo.
+///
+/// When the todo has a parent id but `parent` is `None`, the todo is flagged with
+/// `(parent not found)` instead.
+pub fn todo_breadcrumb(project: &Project, parent: Option<&Todo>, todo: &Todo) -> String {
+    let separator = Text::new(">".to_string()).dimmed().to_string();
+    let mut steps = vec![
+        Text::new(format!("#{} {}", project.id, project.name))
+            .color(project.display_color())
+            .to_string(),
+    ];
+    if let Some(parent) = parent {
+        steps.push(format!("#{} {}", parent.id, parent.title));
+    }
+    let current = format!("#{} {}", todo.id, todo.title);
+    if todo.parent_id.is_some() && parent.is_none() {
+        steps.push(
+            Text::new(format!("{} (parent not found)", current))
+                .warning()
+                .to_string(),
+        );
+    } else {
+        steps.push(Text::new(current).bold().to_string());
+    }
+    steps.join(&format!(" {} ", separator))
+}
+
+impl Project {
+    /// A stable single line summary without colors, e.g. `#3 Website [4 todos]`.
+    pub fn to_oneliner(&self, todo_count: usize) -> String {
+        let plural = if todo_count == 1 { "" } else { "s" };
+        format!("#{} {} [{} todo{}]", self.id, self.name, todo_count, plural)
+    }
+}
+
 impl Display for Project {
     fn to_message(&self, prefix: Option<Prefix>) -> Message {
-        let id = Text::new(format!("#{}", self.id)).padding_right(5);
-        let heading = Text::new(self.name.clone())
-            .color(self.color.clone().unwrap_or_else(|| "#2596be".to_string()))
-            .bold()
-            .padding_left(2);
-
-        let mut message = Message::new().add_line(Text::new(format!("{}{}", id, heading)));
+        let mut message = Message::new().add_line(Text::new(project_heading(self)));
 
         if let Some(p) = prefix {
             message = message.with_prefix(p);
@@ -145,26 +664,556 @@ impl Display for Project {
     }
 }
 
-impl Display for TodoGroup {
+impl Display for ProjectSummary {
     fn to_message(&self, prefix: Option<Prefix>) -> Message {
-        let mut message = self.main_todo.to_message(prefix);
-        for subtodo in self.subtodos.iter() {
-            let sub_message = subtodo.to_message(None);
-            for line in sub_message.lines.into_iter() {
-                message = message.add_line(line);
-            }
+        let mut line = project_heading(&self.project);
+
+        if let Some(overdue) = self.overdue_count {
+            let overdue = Text::new(format!("({} overdue)", overdue))
+                .error()
+                .padding_left(1);
+            line += overdue.to_string().as_str();
+        }
+
+        if let Some(progress) = self.progress {
+            let bar = ProgressBar::new(progress.done, progress.total, env::progress_width_env());
+            line += format!(" {}", bar).as_str();
+        }
+
+        let mut message = Message::new().add_line(Text::new(line));
+
+        if let Some(p) = prefix {
+            message = message.with_prefix(p);
         }
+
         message
     }
 
     fn to_detailed_message(&self, prefix: Option<Prefix>) -> Message {
-        let mut message = self.main_todo.to_detailed_message(prefix);
-        for subtodo in self.subtodos.iter() {
-            let sub_message = subtodo.to_detailed_message(None);
-            for line in sub_message.lines.into_iter() {
-                message = message.add_line(line);
-            }
+        let mut message = self.to_message(prefix);
+
+        if let Some(desc) = &self.project.description {
+            message = message.add_line(Text::new(desc.clone()));
+        };
+
+        if let Some(activity) = &self.last_activity {
+            let line = match (&activity.last_todo_title, activity.last_updated_at) {
+                (Some(title), Some(updated_at)) => format!(
+                    "Last activity: \"{}\" ({})",
+                    title,
+                    format_datetime_relative(updated_at, chrono::Utc::now().naive_utc())
+                ),
+                _ => "Last activity: none".to_string(),
+            };
+            message = message.add_line(Text::new(line).italic());
+        }
+
+        message
+    }
+}
+
+const ONELINER_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+impl Todo {
+    /// A stable single line summary without colors or emoji, for scripts and notifications.
+    pub fn to_oneliner(&self) -> String {
+        let line = format!(
+            "#{} [{}][{}] {}",
+            self.id, self.status, self.priority, self.title
+        );
+        match self.due_date {
+            Some(due_date) => format!(
+                "{} (due: {})",
+                line,
+                due_date.format(ONELINER_DATETIME_FORMAT)
+            ),
+            None => line,
+        }
+    }
+
+    /// Builds the detailed message with a `Blocked by` line for the todo blocking this one.
+    pub fn to_detailed_message_with_blocker(
+        &self,
+        blocker: Option<&Todo>,
+        prefix: Option<Prefix>,
+    ) -> Message {
+        let mut message = self.to_detailed_message(prefix);
+        if let Some(blocker) = blocker {
+            let line = Text::new(format!("Blocked by: #{} {}", blocker.id, blocker.title)).error();
+            message.lines.insert(1, line);
+        }
+        message
+    }
+
+    /// Builds the list view message, which adds an age badge to the summary line.
+    pub fn to_list_message(&self, prefix: Option<Prefix>) -> Message {
+        let mut message = Message::new().add_line(Text::new(todo_line(self, None, true, None)));
+
+        if let Some(p) = prefix {
+            message = message.with_prefix(p);
         }
+
         message
     }
 }
+
+impl TodoGroup {
+    /// Builds the group message with the main todo indented `indent_level` levels
+    /// and its subtodos one level deeper.
+    pub fn to_message_indented(&self, indent_level: usize, prefix: Option<Prefix>) -> Message {
+        indented_group_message(self, indent_level, prefix, |todo| todo.to_message(None))
+    }
+
+    /// Builds the detailed group message with the main todo indented `indent_level` levels
+    /// and its subtodos one level deeper.
+    pub fn to_detailed_message_indented(
+        &self,
+        indent_level: usize,
+        prefix: Option<Prefix>,
+    ) -> Message {
+        indented_group_message(self, indent_level, prefix, |todo| {
+            todo.to_detailed_message(None)
+        })
+    }
+}
+
+// The dimmed `(N subtasks)` suffix of a parent todo, left out when it has no subtodos
+fn subtask_indicator(count: usize) -> Option<Text> {
+    match count {
+        0 => None,
+        1 => Some(Text::new("(1 subtask)".to_string()).dimmed()),
+        n => Some(Text::new(format!("({} subtasks)", n)).dimmed()),
+    }
+}
+
+// The `(~45min, 50min actual)` totals shown after a group's main todo, if it has either
+fn group_time_summary(estimated: Option<i32>, actual: Option<i32>) -> Option<String> {
+    match (estimated, actual) {
+        (Some(estimated), Some(actual)) => {
+            Some(format!("(~{}min, {}min actual)", estimated, actual))
+        }
+        (Some(estimated), None) => Some(format!("(~{}min)", estimated)),
+        (None, Some(actual)) => Some(format!("({}min actual)", actual)),
+        (None, None) => None,
+    }
+}
+
+// Lays out the main todo at `indent_level` and its subtodos one level deeper
+fn indented_group_message(
+    group: &TodoGroup,
+    indent_level: usize,
+    prefix: Option<Prefix>,
+    to_message: impl Fn(&Todo) -> Message,
+) -> Message {
+    let indent_size = env::indent_size_env();
+
+    let mut message = Message::new().with_padding_left(indent_level * indent_size);
+    if let Some(p) = prefix {
+        message = message.with_prefix(p);
+    }
+    let mut main_lines = to_message(&group.main_todo).lines.into_iter();
+    if let Some(first_line) = main_lines.next() {
+        let first_line = match group_time_summary(
+            group.estimated_total_minutes(),
+            group.actual_total_minutes(),
+        ) {
+            Some(summary) => {
+                let total = Text::new(summary).italic();
+                Text::new(format!("{} {}", first_line, total))
+            }
+            None => first_line,
+        };
+        let first_line = match subtask_indicator(group.subtodos.len()) {
+            Some(indicator) => Text::new(format!("{} {}", first_line, indicator)),
+            None => first_line,
+        };
+        message = message.add_line(first_line);
+    }
+    for line in main_lines {
+        message = message.add_line(line);
+    }
+
+    message = message.with_padding_left((indent_level + 1) * indent_size);
+    for subtodo in group.subtodos.iter() {
+        message = message.merge(to_message(subtodo));
+    }
+    message
+}
+
+impl Display for TodoGroup {
+    fn to_message(&self, prefix: Option<Prefix>) -> Message {
+        self.to_message_indented(0, prefix)
+    }
+
+    fn to_detailed_message(&self, prefix: Option<Prefix>) -> Message {
+        self.to_detailed_message_indented(0, prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::sql::ProjectStatus;
+    use chrono::Duration;
+
+    fn create_test_todo(due_date: Option<NaiveDateTime>) -> Todo {
+        Todo {
+            id: 7,
+            project_id: 1,
+            parent_id: None,
+            title: "Fix login".to_string(),
+            description: None,
+            status: TodoStatus::InProgress,
+            priority: TodoPriority::High,
+            due_date,
+            estimated_minutes: None,
+            location: None,
+            url: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            assignee: None,
+            blocked_by_id: None,
+            tags: None,
+            actual_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_format_datetime_aligns_labels() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1)
+            .unwrap()
+            .and_hms_opt(14, 30, 0)
+            .unwrap();
+        let formatted_date = display_datetime(date);
+        let prefix_widths: Vec<usize> = ["Created", "Updated", "Completed"]
+            .iter()
+            .map(|label| {
+                let line = format_datetime(label, date);
+                assert!(line.ends_with(&formatted_date));
+                line.len() - formatted_date.len()
+            })
+            .collect();
+        assert_eq!(prefix_widths, vec![LABEL_WIDTH + 1; 3]);
+        assert!(format_datetime("Completed", date).starts_with("Completed: "));
+    }
+
+    #[test]
+    fn test_todo_breadcrumb() {
+        let project = Project {
+            id: 1,
+            name: "Website".to_string(),
+            description: None,
+            color: None,
+            created_at: None,
+            updated_at: None,
+            status: ProjectStatus::Active,
+            archived_at: None,
+        };
+        let mut parent = create_test_todo(None);
+        parent.id = 3;
+        parent.title = "Launch".to_string();
+        let mut todo = create_test_todo(None);
+        todo.id = 5;
+        todo.parent_id = Some(3);
+
+        let breadcrumb = todo_breadcrumb(&project, Some(&parent), &todo);
+        assert_eq!(breadcrumb, "#1 Website > #3 Launch > #5 Fix login");
+
+        let orphan = todo_breadcrumb(&project, None, &todo);
+        assert_eq!(orphan, "#1 Website > #5 Fix login (parent not found)");
+    }
+
+    #[test]
+    fn test_group_subtask_indicator() {
+        let subtodos = (0..3)
+            .map(|index| {
+                let mut subtodo = create_test_todo(None);
+                subtodo.id = 10 + index;
+                subtodo
+            })
+            .collect();
+        let group = TodoGroup {
+            main_todo: create_test_todo(None),
+            subtodos,
+        };
+        let first_line = group.to_message(None).lines[0].to_string();
+        assert!(first_line.contains("(3 subtasks)"));
+
+        let lonely = TodoGroup {
+            main_todo: create_test_todo(None),
+            subtodos: Vec::new(),
+        };
+        let first_line = lonely.to_message(None).lines[0].to_string();
+        assert!(!first_line.contains("subtask"));
+    }
+
+    #[test]
+    fn test_format_time_comparison() {
+        colored::control::set_override(false);
+        assert_eq!(
+            format_time_comparison(30, 45),
+            "⏱ est: 30min | actual: 45min (50% over)"
+        );
+        assert_eq!(
+            format_time_comparison(60, 30),
+            "⏱ est: 60min | actual: 30min (50% under)"
+        );
+        assert_eq!(
+            format_time_comparison(40, 40),
+            "⏱ est: 40min | actual: 40min (on estimate)"
+        );
+    }
+
+    #[test]
+    fn test_time_variance_boundaries() {
+        assert_eq!(time_variance(60, 30), TimeVariance::Within);
+        assert_eq!(time_variance(60, 60), TimeVariance::Within);
+        assert_eq!(time_variance(100, 101), TimeVariance::SlightlyOver);
+        assert_eq!(time_variance(100, 125), TimeVariance::SlightlyOver);
+        assert_eq!(time_variance(100, 126), TimeVariance::Over);
+        assert_eq!(time_variance(0, 0), TimeVariance::Within);
+        assert_eq!(time_variance(0, 15), TimeVariance::Over);
+    }
+
+    #[test]
+    fn test_additional_lines_compare_estimated_and_actual_minutes() {
+        colored::control::set_override(false);
+        let lines = |est, actual| {
+            let mut todo = create_test_todo(None);
+            todo.estimated_minutes = est;
+            todo.actual_minutes = actual;
+            create_additional_lines(&todo)
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(lines(Some(30), Some(45)), vec!["⏱️  30min / 45min actual"]);
+        assert_eq!(lines(Some(30), None), vec!["⏱️  30min"]);
+        assert_eq!(lines(None, Some(45)), vec!["⏱️  45min actual"]);
+    }
+
+    #[test]
+    fn test_additional_lines_show_assignee() {
+        colored::control::set_override(false);
+        let mut todo = create_test_todo(None);
+        todo.assignee = Some("bob".to_string());
+        todo.location = Some("Office".to_string());
+        let lines: Vec<String> = create_additional_lines(&todo)
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+
+        assert_eq!(lines, vec!["👤 @bob • 📍 Office"]);
+    }
+
+    #[test]
+    fn test_group_time_summary() {
+        assert_eq!(
+            group_time_summary(Some(45), Some(50)),
+            Some("(~45min, 50min actual)".to_string())
+        );
+        assert_eq!(
+            group_time_summary(Some(45), None),
+            Some("(~45min)".to_string())
+        );
+        assert_eq!(
+            group_time_summary(None, Some(50)),
+            Some("(50min actual)".to_string())
+        );
+        assert_eq!(group_time_summary(None, None), None);
+    }
+
+    #[test]
+    fn test_additional_lines_compare_times_when_done() {
+        colored::control::set_override(false);
+        let mut todo = create_test_todo(None);
+        todo.estimated_minutes = Some(30);
+        todo.actual_minutes = Some(45);
+        let has_comparison = |todo: &Todo| {
+            create_additional_lines(todo)
+                .iter()
+                .any(|line| line.to_string() == "⏱ est: 30min | actual: 45min (50% over)")
+        };
+
+        assert!(!has_comparison(&todo));
+        todo.status = TodoStatus::Done;
+        assert!(has_comparison(&todo));
+    }
+
+    #[test]
+    fn test_format_time_comparison_zero_estimate() {
+        colored::control::set_override(false);
+        assert_eq!(format_time_comparison(0, 15), "⏱ est: 0min | actual: 15min");
+    }
+
+    #[test]
+    fn test_workload_message() {
+        colored::control::set_override(false);
+        let line = |minutes| workload_message(minutes).lines[0].to_string();
+        assert_eq!(
+            line(Some(135.0)),
+            "Average workload: ~2h 15min/day (assuming 5-day weeks)"
+        );
+        assert_eq!(
+            line(Some(44.6)),
+            "Average workload: ~45min/day (assuming 5-day weeks)"
+        );
+        assert_eq!(line(None), "Average workload: N/A");
+    }
+
+    #[test]
+    fn test_todo_status_bar() {
+        colored::control::set_override(false);
+        let mut blocked = create_test_todo(None);
+        blocked.status = TodoStatus::Blocked;
+        let todos = vec![create_test_todo(None), create_test_todo(None), blocked];
+
+        assert_eq!(
+            todo_status_bar(&todos),
+            Some("InProgress: 2 • Blocked: 1".to_string())
+        );
+        assert_eq!(todo_status_bar(&[]), None);
+    }
+
+    #[test]
+    fn test_to_debug_message() {
+        let message = create_test_todo(None).to_debug_message();
+        let lines: Vec<String> = message.lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines.first().map(String::as_str), Some("Todo {"));
+        assert!(lines.contains(&"    title: \"Fix login\",".to_string()));
+        assert!(lines.contains(&"    due_date: None,".to_string()));
+    }
+
+    #[test]
+    fn test_project_to_oneliner() {
+        let project = Project {
+            id: 3,
+            name: "Website".to_string(),
+            description: Some("Ignored".to_string()),
+            color: Some("#FF0000".to_string()),
+            created_at: None,
+            updated_at: None,
+            status: ProjectStatus::Active,
+            archived_at: None,
+        };
+        assert_eq!(project.to_oneliner(4), "#3 Website [4 todos]");
+        assert_eq!(project.to_oneliner(1), "#3 Website [1 todo]");
+    }
+
+    fn create_test_project() -> Project {
+        Project {
+            id: 3,
+            name: "Website".to_string(),
+            description: None,
+            color: None,
+            created_at: None,
+            updated_at: None,
+            status: ProjectStatus::Active,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_stats_table() {
+        colored::control::set_override(false);
+        let stats = ProjectStats::from_todos(create_test_project(), &[create_test_todo(None)]);
+        let (headers, rows) = stats_table(&[stats]);
+
+        assert_eq!(headers.len(), rows[0].len());
+        assert_eq!(
+            rows[0],
+            vec!["#3", "Website", "1", "0", "1", "0", "0", "0", "0min", "-"]
+        );
+    }
+
+    #[test]
+    fn test_project_counts_table() {
+        colored::control::set_override(false);
+        let counts = std::collections::HashMap::from([(TodoStatus::ToDo, 2)]);
+        let summaries = [
+            ProjectSummary::new(create_test_project()).with_todo_counts(counts),
+            ProjectSummary::new(create_test_project()).with_todo_counts(Default::default()),
+        ];
+        let (headers, rows) = project_counts_table(&summaries);
+
+        assert_eq!(headers, vec!["id", "project", "todos"]);
+        assert_eq!(rows[0], vec!["#3", "Website", "2 to-do"]);
+        assert_eq!(rows[1][2], "No todos");
+
+        let with_overdue = [ProjectSummary::new(create_test_project()).with_overdue_count(1)];
+        let (headers, rows) = project_counts_table(&with_overdue);
+        assert_eq!(headers, vec!["id", "project", "todos", "overdue"]);
+        assert_eq!(rows[0][3], "1");
+    }
+
+    #[test]
+    fn test_to_oneliner() {
+        assert_eq!(
+            create_test_todo(None).to_oneliner(),
+            "#7 [InProgress][High] Fix login"
+        );
+        let due = NaiveDate::from_ymd_opt(2025, 6, 2)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        assert_eq!(
+            create_test_todo(Some(due)).to_oneliner(),
+            "#7 [InProgress][High] Fix login (due: 2025-06-02 09:30)"
+        );
+    }
+
+    #[test]
+    fn test_format_datetime_clock() {
+        let dt = NaiveDate::from_ymd_opt(2025, 6, 2)
+            .unwrap()
+            .and_hms_opt(15, 5, 0)
+            .unwrap();
+        assert_eq!(format_datetime_clock(dt, false), "Mon Jun 2, 2025 3:05pm");
+        assert_eq!(format_datetime_clock(dt, true), "Mon Jun 2, 2025 15:05");
+    }
+
+    #[test]
+    fn test_age_badge() {
+        colored::control::set_override(false);
+        assert_eq!(age_badge(0, 7).to_string(), "[0d]");
+        assert_eq!(age_badge(12, 7).to_string(), "[12d]");
+    }
+
+    #[test]
+    fn test_format_day_heading_from() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        assert_eq!(format_day_heading_from(today, today), "Today");
+        assert_eq!(
+            format_day_heading_from(today.succ_opt().unwrap(), today),
+            "Tomorrow"
+        );
+        assert_eq!(
+            format_day_heading_from(today.pred_opt().unwrap(), today),
+            "Yesterday"
+        );
+        assert_eq!(
+            format_day_heading_from(NaiveDate::from_ymd_opt(2025, 6, 9).unwrap(), today),
+            "Monday, Jun 9"
+        );
+    }
+
+    #[test]
+    fn test_format_datetime_relative() {
+        let now = chrono::Utc::now().naive_utc();
+        assert_eq!(format_datetime_relative(now, now), "just now");
+        assert_eq!(
+            format_datetime_relative(now - Duration::minutes(1), now),
+            "1 minute ago"
+        );
+        assert_eq!(
+            format_datetime_relative(now - Duration::hours(2), now),
+            "2 hours ago"
+        );
+        assert_eq!(
+            format_datetime_relative(now - Duration::days(3), now),
+            "3 days ago"
+        );
+    }
+}
@@ -74,14 +74,29 @@ impl HexColor {
     }
 
     /// Converts the HexColor back to a hex string
-    pub fn to_hex(&self) -> String {
+    pub fn to_hex(self) -> String {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
     }
 
     /// Returns RGB values as a tuple
-    pub fn to_rgb(&self) -> (u8, u8, u8) {
+    pub fn to_rgb(self) -> (u8, u8, u8) {
         (self.r, self.g, self.b)
     }
+
+    /// Returns the 24-bit ANSI escape sequence that sets this as the foreground color
+    pub fn to_ansi_fg_code(self) -> String {
+        format!("\x1b[38;2;{};{};{}m", self.r, self.g, self.b)
+    }
+
+    /// Returns the 24-bit ANSI escape sequence that sets this as the background color
+    pub fn to_ansi_bg_code(self) -> String {
+        format!("\x1b[48;2;{};{};{}m", self.r, self.g, self.b)
+    }
+}
+
+/// Returns the ANSI escape sequence that resets all colors and styles
+pub fn reset_code() -> &'static str {
+    "\x1b[0m"
 }
 
 impl fmt::Display for HexColor {
@@ -130,6 +145,23 @@ mod tests {
         assert_eq!(color.to_rgb(), (255, 128, 64));
     }
 
+    #[test]
+    fn test_to_ansi_fg_code() {
+        let color = HexColor::new(255, 128, 64);
+        assert_eq!(color.to_ansi_fg_code(), "\x1b[38;2;255;128;64m");
+    }
+
+    #[test]
+    fn test_to_ansi_bg_code() {
+        let color = HexColor::new(0, 9, 10);
+        assert_eq!(color.to_ansi_bg_code(), "\x1b[48;2;0;9;10m");
+    }
+
+    #[test]
+    fn test_reset_code() {
+        assert_eq!(reset_code(), "\x1b[0m");
+    }
+
     #[test]
     fn test_invalid_length() {
         assert!(matches!(
@@ -1,3 +1,5 @@
+use std::io::{self, Write};
+
 use crate::display::text::Text;
 
 #[derive(Debug, Clone, Copy)]
@@ -5,14 +7,20 @@ pub enum Prefix {
     New,
     Update,
     Close,
+    Delete,
+    Reopen,
+    Move,
 }
 
 impl Prefix {
-    fn to_text(&self) -> Text {
+    fn to_text(self) -> Text {
         match self {
             Prefix::New => Text::new("New".to_string()).success().bold(),
             Prefix::Update => Text::new("Updated".to_string()).information().bold(),
             Prefix::Close => Text::new("Closed".to_string()).warning().bold(),
+            Prefix::Delete => Text::new("Deleted".to_string()).error().bold(),
+            Prefix::Reopen => Text::new("Reopened".to_string()).accent().bold(),
+            Prefix::Move => Text::new("Moved".to_string()).information().bold(),
         }
     }
 }
@@ -23,6 +31,12 @@ pub struct Message {
     padding_left: Option<usize>,
 }
 
+impl Default for Message {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Message {
     pub fn new() -> Self {
         Self {
@@ -51,13 +65,126 @@ impl Message {
         self
     }
 
+    /// Appends the lines of `other` as if they were added with `add_line`.
+    ///
+    /// When `other` has a prefix, a blank separator line and the prefix come first.
+    pub fn merge(mut self, other: Message) -> Message {
+        if let Some(prefix) = other.prefix {
+            self = self.with_blank_line().add_line(prefix);
+        }
+        for line in other.lines {
+            self = self.add_line(line);
+        }
+        self
+    }
+
+    /// Appends an empty line, ignoring the left padding.
+    pub fn with_blank_line(mut self) -> Self {
+        self.lines.push(Text::new("".to_string()));
+        self
+    }
+
+    /// The number of lines in the message, not counting the prefix.
+    pub fn lines_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The first line of the message, for contexts that only have room for a summary.
+    pub fn first_line(&self) -> Option<&Text> {
+        self.lines.first()
+    }
+
+    /// Keeps at most `max_lines` lines, followed by a dimmed `... (N more lines)` line when
+    /// any were dropped.
+    pub fn truncate_to(mut self, max_lines: usize) -> Self {
+        let dropped = self.lines.len().saturating_sub(max_lines);
+        if dropped > 0 {
+            self.lines.truncate(max_lines);
+            let noun = if dropped == 1 { "line" } else { "lines" };
+            self = self.add_line(Text::new(format!("... ({} more {})", dropped, noun)).dimmed());
+        }
+        self
+    }
+
     pub fn display(&self) {
+        self.display_to_writer(&mut io::stdout())
+            .expect("Failed to write to stdout");
+    }
+
+    /// Writes the prefix and every line of the message to the given writer.
+    pub fn display_to_writer<W: Write>(&self, w: &mut W) -> io::Result<()> {
         if let Some(p) = &self.prefix {
-            println!("{}", p);
+            writeln!(w, "{}", p)?;
         }
 
         for line in &self.lines {
-            println!("{}", line);
+            writeln!(w, "{}", line)?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_to_writer() {
+        colored::control::set_override(false);
+        let message = Message::new()
+            .with_prefix(Prefix::New)
+            .add_line(Text::new("first".to_string()))
+            .add_line(Text::new("second".to_string()));
+
+        let mut buffer = Vec::new();
+        message.display_to_writer(&mut buffer).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), "New\nfirst\nsecond\n");
+    }
+
+    #[test]
+    fn test_merge() {
+        colored::control::set_override(false);
+        let other = Message::new()
+            .with_prefix(Prefix::Close)
+            .add_line(Text::new("second".to_string()));
+        let message = Message::new()
+            .with_padding_left(2)
+            .add_line(Text::new("first".to_string()))
+            .merge(other);
+
+        let mut buffer = Vec::new();
+        message.display_to_writer(&mut buffer).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "  first\n\n  Closed\n  second\n"
+        );
+    }
+
+    #[test]
+    fn test_truncate_to() {
+        colored::control::set_override(false);
+        let message = (1..=5).fold(Message::new(), |message, index| {
+            message.add_line(Text::new(format!("line {}", index)))
+        });
+        assert_eq!(message.lines_count(), 5);
+        assert_eq!(
+            message.first_line().map(|line| line.to_string()),
+            Some("line 1".to_string())
+        );
+
+        let truncated = message.truncate_to(2);
+        let mut buffer = Vec::new();
+        truncated.display_to_writer(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "line 1\nline 2\n... (3 more lines)\n"
+        );
+
+        let short = Message::new()
+            .add_line(Text::new("only".to_string()))
+            .truncate_to(2);
+        assert_eq!(short.lines_count(), 1);
     }
 }
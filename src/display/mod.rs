@@ -1,43 +1,210 @@
-use std::io::Write;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
 
 use strum::EnumProperty;
 
-use crate::error::TuduError;
+use crate::{
+    arg::{ProjectId, ProjectIdSource},
+    error::{TuduError, TuduResult},
+    infrastructure::env,
+};
 
+#[allow(clippy::module_inception)]
 mod display;
 mod hex;
 mod message;
+mod progress;
+mod table;
 mod text;
 
-pub use display::Display;
-pub use message::Prefix;
+pub use display::{
+    Display, display_datetime, format_day_heading, format_time_comparison, project_counts_table,
+    stats_table, todo_breadcrumb, todo_calendar, todo_status_bar, todo_table, workload_message,
+};
+pub use hex::{HexColor, HexColorError, reset_code};
+pub use message::{Message, Prefix};
+pub use progress::ProgressBar;
+pub use table::{Cell, Table};
 
 pub fn error_message(error: TuduError) {
     let name = error.get_str("Name").expect("Missing Name Property");
     let description = error
         .get_str("Description")
-        .expect("Missing Description Property");
+        .expect("Missing Description Property")
+        .replace("{0}", error.details().unwrap_or_default());
     let cta = error.get_str("Cta").expect("Missing Cta Property");
 
     let line = format!(
         "{} {}: {}\n{}",
         text::Text::new("✖".to_string()).error().bold(),
         text::Text::new(name.to_string()).error().bold(),
-        description.to_string(),
+        description.trim_end(),
         text::Text::new(cta.to_string()).warning().italic()
     );
 
-    writeln!(std::io::stderr(), "{}", line).unwrap()
+    eprintln!("{}", line)
 }
 
-pub fn migration_message(count: usize) {
+pub fn migration_message(migrations: Vec<String>) {
     let success = text::Text::new("Migration successful!".to_string())
         .success()
         .padding_left(5)
         .italic();
-    let message = text::Text::new(format!("Ran {} migrations", count)).padding_left(5);
+    let message = text::Text::new(format!("Ran {} migrations", migrations.len())).padding_left(5);
 
-    writeln!(std::io::stdout(), "{}\n{}", success, message).unwrap();
+    println!("{}\n{}", success, message);
+    for name in migrations {
+        println!("{}", text::Text::new(name).dimmed().padding_left(5));
+    }
+}
+
+pub fn pending_migrations_message(pending: &[String]) {
+    if pending.is_empty() {
+        let up_to_date = text::Text::new("Database is up to date ✓".to_string())
+            .success()
+            .padding_left(5);
+        println!("{}", up_to_date);
+        return;
+    }
+
+    let heading = text::Text::new(format!("{} pending migrations", pending.len()))
+        .warning()
+        .padding_left(5);
+    println!("{}", heading);
+    for name in pending {
+        println!("{}", text::Text::new(name.clone()).padding_left(5));
+    }
+}
+
+pub fn database_check_message(issues: Vec<String>) {
+    if issues.is_empty() {
+        let healthy = text::Text::new("Database is healthy ✓".to_string())
+            .success()
+            .padding_left(5);
+        println!("{}", healthy);
+        return;
+    }
+
+    for issue in issues {
+        let line = text::Text::new(format!("⚠ {}", issue))
+            .warning()
+            .padding_left(5);
+        eprintln!("{}", line);
+    }
+}
+
+/// Opens the writer command output should go to.
+///
+/// When `path` is given, its parent directories are created, the file is truncated and
+/// colors are disabled so the file doesn't fill up with escape codes. Otherwise stdout is used.
+pub fn output_writer(path: Option<&PathBuf>) -> TuduResult<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = File::create(path)?;
+            colored::control::set_override(false);
+            Ok(Box::new(file))
+        }
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+pub fn output_written_message(path: &Path) {
+    let message = text::Text::new(format!("Output written to {}", path.display()))
+        .success()
+        .italic();
+    eprintln!("{}", message);
+}
+
+pub fn warning_message(message: &str, cta: &str) {
+    let line = format!(
+        "{} {}\n{}",
+        text::Text::new("⚠".to_string()).warning().bold(),
+        text::Text::new(message.to_string()).warning(),
+        text::Text::new(cta.to_string()).italic()
+    );
+    eprintln!("{}", line);
+}
+
+pub fn info_message(message: &str) {
+    let line = text::Text::new(message.to_string()).information().italic();
+    eprintln!("{}", line);
+}
+
+/// Tells the user which project a command acts on when the id wasn't passed on the command line.
+pub fn project_id_source_message(project_id: ProjectId, source: &ProjectIdSource) {
+    match source {
+        ProjectIdSource::Flag => {}
+        ProjectIdSource::ConfigFile(path) => info_message(&format!(
+            "Using project #{} from {}",
+            project_id,
+            path.display()
+        )),
+        ProjectIdSource::AutoSelected(name) => info_message(&format!(
+            "Using project #{} {}, the only active project",
+            project_id, name
+        )),
+    }
+}
+
+/// Asks the user a yes/no question on stderr and reads the answer from stdin.
+///
+/// Only `y` or `yes` (any case) confirm. Confirms without asking when `TUDU_YES=1`
+/// is set or stdout is not a terminal, so scripts aren't left waiting.
+pub fn confirm_prompt(message: &str) -> bool {
+    if env::is_auto_confirm() || !io::stdout().is_terminal() {
+        return true;
+    }
+
+    eprint!("{} [y/N]: ", text::Text::new(message.to_string()).bold());
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    is_confirmation(&answer)
+}
+
+fn is_confirmation(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+pub fn config_set_message(key: &str, value: &str, path: &Path) {
+    let success = text::Text::new(format!("Set {}={}", key, value))
+        .success()
+        .padding_left(5);
+    let location = text::Text::new(format!("in {}", path.display()))
+        .italic()
+        .padding_left(5);
+
+    println!("{}\n{}", success, location);
+}
+
+pub fn color_change_message(old_color: Option<String>, new_color: String) {
+    let old = match old_color {
+        Some(color) => format!(
+            "{} {}",
+            text::Text::new("  ".to_string()).on_color(color.clone()),
+            color
+        ),
+        None => "none".to_string(),
+    };
+    let new = format!(
+        "{} {}",
+        text::Text::new("  ".to_string()).on_color(new_color.clone()),
+        new_color
+    );
+
+    println!(
+        "{}",
+        text::Text::new(format!("Color: {} → {}", old, new)).padding_left(5)
+    );
 }
 
 pub fn simple_heading(name: String, color: Option<String>) {
@@ -45,5 +212,73 @@ pub fn simple_heading(name: String, color: Option<String>) {
         .color(color.unwrap_or_else(|| "#2596be".to_string()))
         .bold()
         .padding_left(2);
-    writeln!(std::io::stdout(), "{}", heading).unwrap();
+    println!("{}", heading);
+}
+
+/// Writes rows as an aligned table with a bold header, fitted to the terminal width.
+///
+/// Cells may already be colored, escape codes don't count towards the column widths.
+pub fn print_table<W: Write>(
+    writer: &mut W,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+) -> io::Result<()> {
+    for line in plain_table(headers, rows).render_to_width(env::terminal_width_env()) {
+        writeln!(writer, "{}", line)?;
+    }
+    Ok(())
+}
+
+// Builds a table out of the cell strings
+fn plain_table(headers: Vec<String>, rows: Vec<Vec<String>>) -> Table {
+    rows.into_iter().fold(Table::new(headers), |table, row| {
+        table.add_row(row.into_iter().map(Cell::new).collect())
+    })
+}
+
+/// A bold heading in warning color for a section of a listing.
+pub fn section_heading(name: String) -> message::Message {
+    message::Message::new().add_line(text::Text::new(name).warning().bold())
+}
+
+/// A bold heading for a group of items in a listing.
+pub fn group_heading(name: String) -> message::Message {
+    message::Message::new().add_line(text::Text::new(name).information().bold())
+}
+
+/// A dimmed line closing a listing, e.g. `Showing 1-50 of 142 todos.`
+pub fn footer_message(line: String) -> message::Message {
+    message::Message::new().add_line(text::Text::new(line).dimmed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_table_column_widths() {
+        colored::control::set_override(false);
+        let headers = vec!["name".to_string(), "todos".to_string()];
+        let rows = vec![
+            vec!["Website".to_string(), "3".to_string()],
+            vec!["CLI".to_string(), "12".to_string()],
+        ];
+
+        let lines = plain_table(headers, rows).render_to_width(80);
+
+        assert_eq!(lines[0], "name    | todos");
+        assert_eq!(lines[1], "─".repeat(15));
+        assert_eq!(lines[2], "Website | 3    ");
+        assert_eq!(lines[3], "CLI     | 12   ");
+    }
+
+    #[test]
+    fn test_is_confirmation() {
+        assert!(is_confirmation("y\n"));
+        assert!(is_confirmation("YES"));
+        assert!(!is_confirmation(""));
+        assert!(!is_confirmation("n"));
+        assert!(!is_confirmation("no"));
+        assert!(!is_confirmation("yep"));
+    }
 }
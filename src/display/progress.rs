@@ -0,0 +1,80 @@
+use std::fmt;
+
+use crate::display::text::Text;
+
+const FILLED: char = '▓';
+const EMPTY: char = '░';
+const NO_TODOS: char = '─';
+
+/// A text progress bar showing how many of a project's todos are done.
+pub struct ProgressBar {
+    done: i64,
+    total: i64,
+    width: usize,
+}
+
+impl ProgressBar {
+    /// Creates a new progress bar for `done` out of `total` todos.
+    pub fn new(done: i64, total: i64, width: usize) -> Self {
+        Self { done, total, width }
+    }
+
+    /// The completed share as a whole percentage between 0 and 100.
+    pub fn percentage(&self) -> i64 {
+        if self.total <= 0 {
+            return 0;
+        }
+        (self.done.clamp(0, self.total) * 100) / self.total
+    }
+
+    // The number of bar characters that are filled in
+    fn filled_width(&self) -> usize {
+        if self.total <= 0 {
+            return 0;
+        }
+        let done = self.done.clamp(0, self.total) as usize;
+        (done * self.width) / self.total as usize
+    }
+}
+
+impl fmt::Display for ProgressBar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total <= 0 {
+            let empty = format!("[{}] No todos", NO_TODOS.to_string().repeat(self.width));
+            return write!(f, "{}", Text::new(empty).dimmed());
+        }
+
+        let filled = self.filled_width();
+        write!(
+            f,
+            "[{}{}] {}% ({}/{})",
+            FILLED.to_string().repeat(filled),
+            EMPTY.to_string().repeat(self.width - filled),
+            self.percentage(),
+            self.done,
+            self.total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_bar() {
+        let bar = ProgressBar::new(4, 10, 10);
+        assert_eq!(bar.to_string(), "[▓▓▓▓░░░░░░] 40% (4/10)");
+
+        let bar = ProgressBar::new(1, 3, 10);
+        assert_eq!(bar.percentage(), 33);
+        assert_eq!(bar.to_string(), "[▓▓▓░░░░░░░] 33% (1/3)");
+    }
+
+    #[test]
+    fn test_progress_bar_no_todos() {
+        colored::control::set_override(false);
+        let bar = ProgressBar::new(0, 0, 5);
+        assert_eq!(bar.to_string(), "[─────] No todos");
+    }
+}
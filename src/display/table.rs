@@ -0,0 +1,167 @@
+use crate::display::text::{Text, truncate_visible, visible_len};
+
+const SEPARATOR: &str = " | ";
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// A single table cell. Its text may already be colored, escape codes don't count as width.
+pub struct Cell {
+    text: String,
+}
+
+impl Cell {
+    /// Creates a new cell.
+    pub fn new(text: String) -> Self {
+        Self { text }
+    }
+}
+
+/// A simple text table with a bold header row and aligned columns.
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Creates a new table with the given column headers.
+    pub fn new(headers: Vec<String>) -> Self {
+        Self {
+            headers,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row of cells. Missing cells are rendered empty.
+    pub fn add_row(mut self, row: Vec<Cell>) -> Self {
+        self.rows.push(row);
+        self
+    }
+
+    /// The width of each column when nothing has to be shrunk.
+    fn natural_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| visible_len(h)).collect();
+        for row in &self.rows {
+            for (index, cell) in row.iter().enumerate().take(widths.len()) {
+                widths[index] = widths[index].max(visible_len(&cell.text));
+            }
+        }
+        widths
+    }
+
+    /// The width of each column so that a rendered row fits in `width` characters.
+    ///
+    /// Columns are shrunk in proportion to their natural width, but never below a small minimum.
+    fn column_widths(&self, width: usize) -> Vec<usize> {
+        let natural = self.natural_widths();
+        let separators = SEPARATOR.len() * natural.len().saturating_sub(1);
+        let natural_total: usize = natural.iter().sum();
+        let available = width.saturating_sub(separators);
+
+        if natural_total <= available {
+            return natural;
+        }
+
+        natural
+            .iter()
+            .map(|w| (w * available / natural_total).max(MIN_COLUMN_WIDTH.min(*w)))
+            .collect()
+    }
+
+    /// Renders the table into lines no wider than `width` visible characters.
+    pub fn render_to_width(&self, width: usize) -> Vec<String> {
+        let widths = self.column_widths(width);
+        let mut lines = Vec::new();
+
+        let header = self
+            .headers
+            .iter()
+            .zip(widths.iter())
+            .map(|(h, w)| Text::new(fit(h, *w)).bold().to_string())
+            .collect::<Vec<String>>()
+            .join(SEPARATOR);
+        lines.push(header);
+
+        let rule_width =
+            widths.iter().sum::<usize>() + SEPARATOR.len() * widths.len().saturating_sub(1);
+        lines.push("─".repeat(rule_width));
+
+        for row in &self.rows {
+            let line = widths
+                .iter()
+                .enumerate()
+                .map(|(index, w)| match row.get(index) {
+                    Some(cell) => fit(&cell.text, *w),
+                    None => " ".repeat(*w),
+                })
+                .collect::<Vec<String>>()
+                .join(SEPARATOR);
+            lines.push(line);
+        }
+
+        lines
+    }
+}
+
+// Pads `s` to exactly `width` characters. Too long text keeps one character less than
+// `Text::truncate` would so the `…` still fits in the column
+fn fit(s: &str, width: usize) -> String {
+    let count = visible_len(s);
+    if count <= width {
+        format!("{}{}", s, " ".repeat(width - count))
+    } else if width == 0 {
+        String::new()
+    } else {
+        truncate_visible(s, width - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_table() -> Table {
+        Table::new(vec!["id".to_string(), "title".to_string()])
+            .add_row(vec![
+                Cell::new("1".to_string()),
+                Cell::new("A fairly long title".to_string()),
+            ])
+            .add_row(vec![
+                Cell::new("22".to_string()),
+                Cell::new("Short".to_string()),
+            ])
+    }
+
+    #[test]
+    fn test_render_to_width_natural() {
+        colored::control::set_override(false);
+        let lines = test_table().render_to_width(100);
+
+        assert_eq!(lines[0], "id | title              ");
+        assert_eq!(lines[2], "1  | A fairly long title");
+        assert_eq!(lines[3], "22 | Short              ");
+    }
+
+    #[test]
+    fn test_render_to_width_shrinks_columns() {
+        colored::control::set_override(false);
+        let lines = test_table().render_to_width(15);
+
+        for line in &lines {
+            assert!(line.chars().count() <= 15, "{} is too wide", line);
+        }
+        assert!(lines[2].ends_with('…'));
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 4), "abc…");
+        assert_eq!(fit("abc", 0), "");
+    }
+
+    #[test]
+    fn test_fit_colored_text() {
+        let red = "\x1b[31mab\x1b[0m";
+        assert_eq!(fit(red, 4), format!("{}  ", red));
+        assert_eq!(visible_len(&fit("\x1b[31mabcdef\x1b[0m", 4)), 4);
+    }
+}
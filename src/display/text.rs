@@ -30,6 +30,15 @@ impl Text {
         self
     }
 
+    pub fn on_color(mut self, color: String) -> Self {
+        let hex_color = hex::HexColor::from_hex(color.as_str()).expect("Invalid hex code");
+        let (red, green, blue) = hex_color.to_rgb();
+        self.text = self
+            .text
+            .on_custom_color(CustomColor::new(red, green, blue));
+        self
+    }
+
     pub fn error(mut self) -> Self {
         self.text = self.text.red();
         self
@@ -40,6 +49,11 @@ impl Text {
         self
     }
 
+    pub fn accent(mut self) -> Self {
+        self.text = self.text.magenta();
+        self
+    }
+
     /// Padding-left
     pub fn padding_left(mut self, width: usize) -> Self {
         let spaces = " ".repeat(width);
@@ -62,6 +76,68 @@ impl Text {
         self.text = self.text.italic();
         self
     }
+
+    pub fn dimmed(mut self) -> Self {
+        self.text = self.text.dimmed();
+        self
+    }
+
+    /// Shortens the text to its first `width` visible characters followed by `…` when it is
+    /// longer than that.
+    ///
+    /// ANSI escape codes inside the text don't count towards the width and are kept.
+    pub fn truncate(mut self, width: usize) -> Self {
+        if visible_len(&self.text.input) > width {
+            self.text.input = truncate_visible(&self.text.input, width);
+        }
+        self
+    }
+}
+
+/// Counts the characters of `s` that are shown, skipping `\x1b[...m` escape sequences.
+pub fn visible_len(s: &str) -> usize {
+    let mut count = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Keeps the first `width` visible characters of `s` followed by `…`, copying escape sequences
+/// as is.
+pub fn truncate_visible(s: &str, width: usize) -> String {
+    let mut truncated = String::new();
+    let mut has_escapes = false;
+    let mut count = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            has_escapes = true;
+            truncated.push(c);
+            for c in chars.by_ref() {
+                truncated.push(c);
+                if c == 'm' {
+                    break;
+                }
+            }
+        } else if count < width {
+            truncated.push(c);
+            count += 1;
+        } else {
+            break;
+        }
+    }
+    truncated.push('…');
+    // Colors started inside the text would otherwise run on past the cut
+    if has_escapes {
+        truncated.push_str("\x1b[0m");
+    }
+    truncated
 }
 
 impl Display for Text {
@@ -69,3 +145,38 @@ impl Display for Text {
         write!(f, "{}", self.text)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_len() {
+        assert_eq!(visible_len("plain"), 5);
+        assert_eq!(visible_len("\x1b[1;31mred\x1b[0m"), 3);
+    }
+
+    #[test]
+    fn test_truncate_colored_text() {
+        let colored = format!("\x1b[38;2;255;0;0m{}\x1b[0m", "a".repeat(60));
+        let text = Text::new(colored).truncate(50);
+        let truncated = &text.text.input;
+
+        assert_eq!(visible_len(truncated), 51);
+        assert!(truncated.starts_with("\x1b[38;2;255;0;0m"));
+        assert!(truncated.ends_with(&format!("{}…\x1b[0m", "a".repeat(50))));
+    }
+
+    #[test]
+    fn test_truncate_short_text() {
+        colored::control::set_override(false);
+        assert_eq!(
+            Text::new("short".to_string()).truncate(50).to_string(),
+            "short"
+        );
+        assert_eq!(
+            Text::new("abcdef".to_string()).truncate(4).to_string(),
+            "abcd…"
+        );
+    }
+}
@@ -2,7 +2,7 @@ use strum::EnumProperty;
 
 pub type TuduResult<T> = Result<T, TuduError>;
 
-#[derive(EnumProperty)]
+#[derive(Debug, PartialEq, EnumProperty)]
 pub enum TuduError {
     // A temporary Error since Prompt is not implemented
     #[strum(props(
@@ -34,6 +34,13 @@ pub enum TuduError {
     ))]
     RequiredArgumentError,
 
+    #[strum(props(
+        Name = "MissingEnvError",
+        Description = "A required environment variable is not set. {0}",
+        Cta = "Please set the environment variable and try again."
+    ))]
+    MissingEnvError(String),
+
     #[strum(props(
         Name = "DatabaseError",
         Description = "An error occurred in your database. {0}",
@@ -47,11 +54,52 @@ pub enum TuduError {
         Cta = "Please try again!!"
     ))]
     UnSupportedError(String),
+
+    #[strum(props(
+        Name = "IoError",
+        Description = "An error occurred while reading or writing a file. {0}",
+        Cta = "Please check the path and its permissions and try again."
+    ))]
+    IoError(String),
+
+    #[strum(props(
+        Name = "ValidationError",
+        Description = "The change you asked for isn't allowed. {0}",
+        Cta = "Please check the values and try again."
+    ))]
+    ValidationError(String),
+}
+
+impl From<std::io::Error> for TuduError {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value.to_string())
+    }
+}
+
+impl TuduError {
+    /// The details that fill the `{0}` placeholder of the description, if any.
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            TuduError::MissingEnvError(details)
+            | TuduError::DatabaseError(details)
+            | TuduError::UnSupportedError(details)
+            | TuduError::IoError(details)
+            | TuduError::ValidationError(details) => Some(details.as_str()),
+            _ => None,
+        }
+    }
 }
 
 impl From<diesel::result::Error> for TuduError {
     fn from(value: diesel::result::Error) -> Self {
         match value {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::CheckViolation,
+                database_error_information,
+            ) => Self::DatabaseError(format!(
+                "A value is outside the range the database allows. {}",
+                database_error_information.message()
+            )),
             diesel::result::Error::DatabaseError(_, database_error_information) => {
                 Self::DatabaseError(database_error_information.message().to_string())
             }
@@ -68,3 +116,44 @@ impl From<diesel::result::Error> for TuduError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_equality() {
+        let result: TuduResult<()> = Err(TuduError::RequiredArgumentError);
+        assert_eq!(result, Err(TuduError::RequiredArgumentError));
+        assert_ne!(
+            TuduError::RequiredArgumentError,
+            TuduError::CommandRequiredError
+        );
+    }
+
+    #[test]
+    fn test_error_equality_compares_details() {
+        assert_eq!(
+            TuduError::DatabaseError("locked".to_string()),
+            TuduError::DatabaseError("locked".to_string())
+        );
+        assert_ne!(
+            TuduError::DatabaseError("locked".to_string()),
+            TuduError::DatabaseError("corrupt".to_string())
+        );
+        assert_ne!(
+            TuduError::DatabaseError("locked".to_string()),
+            TuduError::IoError("locked".to_string())
+        );
+    }
+
+    #[test]
+    fn test_from_not_found() {
+        assert_eq!(
+            TuduError::from(diesel::result::Error::NotFound),
+            TuduError::DatabaseError(
+                "No rows were returned by a query expected to return at least one row.".to_string()
+            )
+        );
+    }
+}
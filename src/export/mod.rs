@@ -0,0 +1,498 @@
+use chrono::NaiveDateTime;
+use clap::{ArgMatches, Command, ValueEnum};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    arg::{ProjectId, TuduArg, get_negatable_flag},
+    display::{output_writer, output_written_message},
+    error::TuduResult,
+    project::{query::ProjectQuery, sql::Project},
+    schema::{projects::dsl as projects_dsl, todos::dsl as todos_dsl},
+    todo::{
+        group::{TodoGroup, organize_todos_hierarchically},
+        sql::{Todo, TodoStatus},
+    },
+};
+
+/// A project and its todos, grouped under their parents, as written by `tudu export json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectExport {
+    /// The exported project.
+    pub project: Project,
+    /// The todos of the project, with subtodos nested under their parent.
+    pub todos: Vec<TodoGroup>,
+}
+
+impl ProjectExport {
+    /// The export as JSON, made of the project's and the todos' own JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        let todos: Vec<serde_json::Value> = self
+            .todos
+            .iter()
+            .map(|group| {
+                serde_json::json!({
+                    "main_todo": group.main_todo.to_json(),
+                    "subtodos": group.subtodos.iter().map(Todo::to_json).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "project": self.project.to_json(),
+            "todos": todos,
+        })
+    }
+}
+
+pub fn export_json_command() -> Command {
+    Command::new("json")
+        .about("Write projects and their todos to stdout as JSON")
+        .arg(TuduArg::ProjectId.into_arg(true))
+        .args(TuduArg::IncludeDone.into_negatable_args(true))
+}
+
+// Loads every project, or only `project_id`, with its todos grouped under their parents.
+// Fails with `NotFound` when `project_id` does not exist, like `tudu stats`
+fn load_project_exports(
+    conn: &mut SqliteConnection,
+    project_id: Option<ProjectId>,
+    include_done: bool,
+) -> Result<Vec<ProjectExport>, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let mut query = ProjectQuery::new()
+            .include_inactive()
+            .build()
+            .order(projects_dsl::id.asc());
+        if let Some(project_id) = project_id {
+            query = query.filter(projects_dsl::id.eq(project_id));
+        }
+        let projects = query.load::<Project>(conn)?;
+        if project_id.is_some() && projects.is_empty() {
+            return Err(diesel::result::Error::NotFound);
+        }
+
+        let mut exports = Vec::with_capacity(projects.len());
+        for project in projects {
+            let todos = todos_dsl::todos
+                .filter(todos_dsl::project_id.eq(project.id))
+                .load::<Todo>(conn)?;
+            // Filtered after grouping, since grouping drops subtodos whose parent isn't loaded
+            let mut groups = organize_todos_hierarchically(todos);
+            if !include_done {
+                groups = without_done_todos(groups);
+            }
+            exports.push(ProjectExport {
+                project,
+                todos: groups,
+            });
+        }
+        Ok(exports)
+    })
+}
+
+// Drops the done todos from `groups`. The open subtodos of a done parent become groups of their own
+fn without_done_todos(groups: Vec<TodoGroup>) -> Vec<TodoGroup> {
+    let mut open_groups = Vec::with_capacity(groups.len());
+    for group in groups {
+        let open_subtodos = group
+            .subtodos
+            .into_iter()
+            .filter(|todo| todo.status != TodoStatus::Done);
+        if group.main_todo.status == TodoStatus::Done {
+            open_groups.extend(open_subtodos.map(TodoGroup::new));
+        } else {
+            let mut open_group = TodoGroup::new(group.main_todo);
+            for subtodo in open_subtodos {
+                open_group.add_subtodo(subtodo);
+            }
+            open_groups.push(open_group);
+        }
+    }
+    open_groups
+}
+
+/// Writes every project, or the one passed with `--project_id`, as a pretty printed JSON array.
+pub fn handle_export_json_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let project_id = matches
+        .get_one::<i32>(TuduArg::ProjectId.name())
+        .copied()
+        .map(ProjectId);
+    let include_done = get_negatable_flag(matches, TuduArg::IncludeDone, false);
+
+    let exports = load_project_exports(conn, project_id, include_done)?;
+    let json = serde_json::Value::Array(exports.iter().map(ProjectExport::to_json).collect());
+    writeln!(io::stdout(), "{:#}", json)?;
+    Ok(())
+}
+
+pub fn export_markdown_command() -> Command {
+    Command::new("markdown")
+        .about("Write projects as GitHub-flavored Markdown task lists")
+        .alias("md")
+        .arg(TuduArg::ProjectId.into_arg(true))
+        .arg(TuduArg::Output.into_arg(true))
+        .args(TuduArg::IncludeDone.into_negatable_args(true))
+}
+
+// A `- [ ] **[HIGH]** title` task list item, checked once the todo is done or cancelled
+fn markdown_task(todo: &Todo, indent: usize) -> String {
+    let checkbox = if todo.status.is_terminal() { "x" } else { " " };
+    format!(
+        "{}- [{}] **[{}]** {}",
+        " ".repeat(indent),
+        checkbox,
+        todo.priority.to_string().to_uppercase(),
+        todo.title
+    )
+}
+
+// The project as a heading, its description and a task list with subtodos indented under their
+// parent
+fn project_markdown(export: &ProjectExport) -> String {
+    let mut lines = vec![format!("# {}", export.project.name), String::new()];
+    if let Some(description) = &export.project.description {
+        lines.push(description.clone());
+        lines.push(String::new());
+    }
+    for group in &export.todos {
+        lines.push(markdown_task(&group.main_todo, 0));
+        for subtodo in &group.subtodos {
+            lines.push(markdown_task(subtodo, 2));
+        }
+    }
+    lines.join("\n").trim_end().to_string() + "\n"
+}
+
+/// Writes every project, or the one passed with `--project_id`, as Markdown task lists.
+///
+/// Done todos are included unless `--no_include_done` is passed, so they show up checked.
+pub fn handle_export_markdown_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let project_id = matches
+        .get_one::<i32>(TuduArg::ProjectId.name())
+        .copied()
+        .map(ProjectId);
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let include_done = get_negatable_flag(matches, TuduArg::IncludeDone, true);
+
+    let exports = load_project_exports(conn, project_id, include_done)?;
+    let markdown: Vec<String> = exports.iter().map(project_markdown).collect();
+
+    let mut writer = output_writer(output)?;
+    write!(writer, "{}", markdown.join("\n"))?;
+    if let Some(path) = output {
+        output_written_message(path);
+    }
+    Ok(())
+}
+
+const CSV_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+// The columns of `tudu export csv`, one for every `Todo` field in declaration order
+const CSV_HEADERS: [&str; 18] = [
+    "id",
+    "project_id",
+    "parent_id",
+    "title",
+    "description",
+    "status",
+    "priority",
+    "due_date",
+    "estimated_minutes",
+    "location",
+    "url",
+    "created_at",
+    "updated_at",
+    "completed_at",
+    "assignee",
+    "blocked_by_id",
+    "tags",
+    "actual_minutes",
+];
+
+pub fn export_csv_command() -> Command {
+    Command::new("csv")
+        .about("Write todos to stdout as CSV, one row per todo")
+        .arg(TuduArg::ProjectId.into_arg(true))
+        .arg(TuduArg::Output.into_arg(true))
+        .args(TuduArg::IncludeDone.into_negatable_args(true))
+}
+
+// Quotes the field when it contains a delimiter, quote or line break, doubling inner quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// The CLI name of an enum value, e.g. `in-progress`, so exports can be fed back into tudu
+fn csv_enum<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn csv_datetime(value: Option<NaiveDateTime>) -> String {
+    value
+        .map(|dt| dt.format(CSV_DATETIME_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn csv_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+// A single CSV record for the todo, in the order of `CSV_HEADERS`, without the line ending
+fn csv_row(todo: &Todo) -> String {
+    let fields = [
+        todo.id.to_string(),
+        todo.project_id.to_string(),
+        csv_optional(todo.parent_id),
+        todo.title.clone(),
+        csv_optional(todo.description.as_ref()),
+        csv_enum(&todo.status),
+        csv_enum(&todo.priority),
+        csv_datetime(todo.due_date),
+        csv_optional(todo.estimated_minutes),
+        csv_optional(todo.location.as_ref()),
+        csv_optional(todo.url.as_ref()),
+        csv_datetime(todo.created_at),
+        csv_datetime(todo.updated_at),
+        csv_datetime(todo.completed_at),
+        csv_optional(todo.assignee.as_ref()),
+        csv_optional(todo.blocked_by_id),
+        csv_optional(todo.tags.as_ref()),
+        csv_optional(todo.actual_minutes),
+    ];
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Writes the todos of every project, or the one passed with `--project_id`, as RFC 4180 CSV
+/// with a header row.
+pub fn handle_export_csv_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let project_id = matches
+        .get_one::<i32>(TuduArg::ProjectId.name())
+        .copied()
+        .map(ProjectId);
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let include_done = get_negatable_flag(matches, TuduArg::IncludeDone, false);
+
+    let mut query = todos_dsl::todos
+        .order((todos_dsl::project_id.asc(), todos_dsl::id.asc()))
+        .into_boxed();
+    if let Some(project_id) = project_id {
+        query = query.filter(todos_dsl::project_id.eq(project_id));
+    }
+    if !include_done {
+        query = query.filter(todos_dsl::status.ne(TodoStatus::Done));
+    }
+    let todos = query.load::<Todo>(conn)?;
+
+    let mut writer = output_writer(output)?;
+    write!(writer, "{}\r\n", CSV_HEADERS.join(","))?;
+    for todo in &todos {
+        write!(writer, "{}\r\n", csv_row(todo))?;
+    }
+    if let Some(path) = output {
+        output_written_message(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arg::TodoId,
+        infrastructure::database::{
+            in_memory_connection, insert_test_project, insert_test_todo, test_new_todo,
+        },
+        todo::sql::{NewTodo, TodoPriority},
+    };
+
+    fn insert_todo(
+        conn: &mut SqliteConnection,
+        project_id: i32,
+        parent_id: Option<i32>,
+        title: &str,
+        status: TodoStatus,
+    ) -> Todo {
+        insert_test_todo(
+            conn,
+            NewTodo {
+                parent_id: parent_id.map(TodoId),
+                description: Some(format!("About {}", title)),
+                status,
+                priority: TodoPriority::Medium,
+                estimated_minutes: Some(15),
+                url: Some("https://example.com".to_string()),
+                assignee: Some("sam".to_string()),
+                ..test_new_todo(project_id, title)
+            },
+        )
+    }
+
+    #[test]
+    fn test_export_round_trips_through_json() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Export");
+        diesel::update(projects_dsl::projects.find(project.id))
+            .set((
+                projects_dsl::description.eq("Everything"),
+                projects_dsl::color.eq("#123456"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        let parent = insert_todo(&mut conn, project.id, None, "Parent", TodoStatus::ToDo);
+        insert_todo(
+            &mut conn,
+            project.id,
+            Some(parent.id),
+            "Child",
+            TodoStatus::InProgress,
+        );
+        insert_todo(&mut conn, project.id, None, "Finished", TodoStatus::Done);
+
+        let exports = load_project_exports(&mut conn, Some(ProjectId(project.id)), false).unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].todos.len(), 1);
+        assert_eq!(exports[0].todos[0].subtodos.len(), 1);
+
+        let json = format!("{:#}", exports[0].to_json());
+        let round_tripped: ProjectExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.to_json(), exports[0].to_json());
+        assert_eq!(
+            exports[0].to_json(),
+            serde_json::to_value(&exports[0]).unwrap()
+        );
+
+        let with_done = load_project_exports(&mut conn, None, true).unwrap();
+        assert_eq!(with_done[0].todos.len(), 2);
+    }
+
+    #[test]
+    fn test_export_keeps_open_subtodos_of_done_parents() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Orphans");
+        let parent = insert_todo(&mut conn, project.id, None, "Parent", TodoStatus::Done);
+        insert_todo(
+            &mut conn,
+            project.id,
+            Some(parent.id),
+            "Open child",
+            TodoStatus::ToDo,
+        );
+        insert_todo(
+            &mut conn,
+            project.id,
+            Some(parent.id),
+            "Done child",
+            TodoStatus::Done,
+        );
+
+        let exports = load_project_exports(&mut conn, Some(ProjectId(project.id)), false).unwrap();
+        let titles: Vec<&str> = exports[0]
+            .todos
+            .iter()
+            .map(|group| group.main_todo.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Open child"]);
+        assert!(exports[0].todos[0].subtodos.is_empty());
+        assert_eq!(
+            project_markdown(&exports[0]),
+            "# Orphans\n\n- [ ] **[MEDIUM]** Open child\n"
+        );
+
+        let with_done = load_project_exports(&mut conn, Some(ProjectId(project.id)), true).unwrap();
+        assert_eq!(with_done[0].todos.len(), 1);
+        assert_eq!(with_done[0].todos[0].subtodos.len(), 2);
+    }
+
+    #[test]
+    fn test_export_unknown_project_is_not_found() {
+        let mut conn = in_memory_connection();
+        assert!(matches!(
+            load_project_exports(&mut conn, Some(ProjectId(999)), true),
+            Err(diesel::result::Error::NotFound)
+        ));
+        assert!(
+            load_project_exports(&mut conn, None, true)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_csv_row_matches_headers() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Csv");
+        let todo = insert_todo(
+            &mut conn,
+            project.id,
+            None,
+            "Buy milk, eggs",
+            TodoStatus::InProgress,
+        );
+
+        let row = csv_row(&todo);
+        let created_at = todo.created_at.unwrap().format(CSV_DATETIME_FORMAT);
+        assert_eq!(
+            row,
+            format!(
+                "{},{},,\"Buy milk, eggs\",\"About Buy milk, eggs\",in-progress,medium,,15,,https://example.com,{},{},,sam,,,",
+                todo.id, project.id, created_at, created_at
+            )
+        );
+    }
+
+    #[test]
+    fn test_project_markdown() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Garden");
+        diesel::update(projects_dsl::projects.find(project.id))
+            .set(projects_dsl::description.eq("Spring chores"))
+            .execute(&mut conn)
+            .unwrap();
+        let parent = insert_todo(&mut conn, project.id, None, "Plant", TodoStatus::ToDo);
+        insert_todo(
+            &mut conn,
+            project.id,
+            Some(parent.id),
+            "Buy seeds",
+            TodoStatus::Done,
+        );
+
+        let exports = load_project_exports(&mut conn, Some(ProjectId(project.id)), true).unwrap();
+        assert_eq!(
+            project_markdown(&exports[0]),
+            "# Garden\n\nSpring chores\n\n- [ ] **[MEDIUM]** Plant\n  - [x] **[MEDIUM]** Buy seeds\n"
+        );
+    }
+}
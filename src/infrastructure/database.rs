@@ -1,24 +1,182 @@
-use diesel::{Connection, SqliteConnection};
+use diesel::{
+    Connection, QueryableByName, RunQueryDsl, SqliteConnection, sql_query,
+    sql_types::{BigInt, Nullable, Text},
+};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 
 use crate::{
+    display::info_message,
     error::{TuduError, TuduResult},
     infrastructure::env,
 };
+use std::fs;
+
+#[cfg(test)]
+use crate::{
+    arg::ProjectId,
+    project::sql::{NewProject, Project},
+    todo::sql::{NewTodo, Todo, TodoPriority, TodoStatus},
+};
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
 pub fn database_connection() -> SqliteConnection {
     let database_url = env::database_url_env();
     let database_url = database_url.to_str().unwrap();
+    if env::is_debug_mode() {
+        eprintln!("Connecting to database at {}", database_url);
+    }
     SqliteConnection::establish(database_url)
         .unwrap_or_else(|_| panic!("Error connecting to {}", database_url))
 }
 
-pub fn run_database_migrations() -> TuduResult<usize> {
-    let mut connection = database_connection();
-    let success = connection
-        .run_pending_migrations(MIGRATIONS)
+/// Runs the pending migrations and returns their names, e.g. `2024-01-01-000001_create_todos`.
+pub fn run_database_migrations(conn: &mut SqliteConnection) -> TuduResult<Vec<String>> {
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| TuduError::DatabaseError(e.to_string()))?;
+    let names = pending
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+    conn.run_migrations(&pending)
+        .map_err(|e| TuduError::DatabaseError(e.to_string()))?;
+    Ok(names)
+}
+
+/// Opens a fresh in-memory database with every migration applied, for tests.
+#[cfg(test)]
+pub fn in_memory_connection() -> SqliteConnection {
+    let mut connection = SqliteConnection::establish(":memory:").unwrap();
+    connection.run_pending_migrations(MIGRATIONS).unwrap();
+    connection
+}
+
+/// Inserts an active project named `name` without a description or color, for tests.
+#[cfg(test)]
+pub fn insert_test_project(conn: &mut SqliteConnection, name: &str) -> Project {
+    diesel::insert_into(crate::schema::projects::table)
+        .values(NewProject {
+            name: name.to_string(),
+            description: None,
+            color: None,
+        })
+        .get_result::<Project>(conn)
+        .unwrap()
+}
+
+/// Builds a low priority to-do with every optional field empty, for tests.
+///
+/// Override the fields a test cares about with struct update syntax:
+/// `NewTodo { status: TodoStatus::Done, ..test_new_todo(project.id, "Task") }`.
+#[cfg(test)]
+pub fn test_new_todo(project_id: i32, title: &str) -> NewTodo {
+    let now = chrono::Utc::now().naive_utc();
+    NewTodo {
+        project_id: ProjectId(project_id),
+        parent_id: None,
+        title: title.to_string(),
+        description: None,
+        status: TodoStatus::ToDo,
+        priority: TodoPriority::Low,
+        due_date: None,
+        estimated_minutes: None,
+        location: None,
+        url: None,
+        created_at: Some(now),
+        updated_at: Some(now),
+        completed_at: None,
+        assignee: None,
+        tags: None,
+        actual_minutes: None,
+    }
+}
+
+/// Inserts `new_todo` and returns the stored row, for tests.
+#[cfg(test)]
+pub fn insert_test_todo(conn: &mut SqliteConnection, new_todo: NewTodo) -> Todo {
+    diesel::insert_into(crate::schema::todos::table)
+        .values(new_todo)
+        .get_result::<Todo>(conn)
+        .unwrap()
+}
+
+/// Lists the names of the migrations that have not been run against the database yet.
+pub fn list_pending_migrations(conn: &mut SqliteConnection) -> TuduResult<Vec<String>> {
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
         .map_err(|e| TuduError::DatabaseError(e.to_string()))?;
-    Ok(success.len())
+    Ok(pending
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
+
+/// Creates the database and runs the migrations when the database file does not exist yet.
+///
+/// This lets new users start using tudu without running `tudu migrations` first.
+pub fn ensure_database_exists() -> TuduResult<()> {
+    let database_path = env::database_url_env();
+    if fs::metadata(&database_path).is_ok() {
+        return Ok(());
+    }
+
+    info_message(&format!(
+        "Welcome to tudu! Creating your database at {}...",
+        database_path.display()
+    ));
+    if let Some(parent) = database_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    run_database_migrations(&mut database_connection())?;
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct IntegrityCheckRow {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyCheckRow {
+    #[diesel(sql_type = Text)]
+    table: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    rowid: Option<i64>,
+    #[diesel(sql_type = Text)]
+    parent: String,
+}
+
+/// Runs SQLite's `integrity_check` and `foreign_key_check` pragmas against the database.
+///
+/// # Returns
+///
+/// Returns a list of human readable issues. An empty list means the database is healthy.
+pub fn check_database_integrity(conn: &mut SqliteConnection) -> TuduResult<Vec<String>> {
+    let mut issues = Vec::new();
+
+    let integrity_rows = sql_query("PRAGMA integrity_check").load::<IntegrityCheckRow>(conn)?;
+    for row in integrity_rows {
+        if row.integrity_check != "ok" {
+            issues.push(row.integrity_check);
+        }
+    }
+
+    let foreign_key_rows =
+        sql_query("PRAGMA foreign_key_check").load::<ForeignKeyCheckRow>(conn)?;
+    for row in foreign_key_rows {
+        let rowid = row
+            .rowid
+            .map(|id| format!("#{}", id))
+            .unwrap_or_else(|| "?".to_string());
+        issues.push(format!(
+            "Row {} in `{}` references a missing row in `{}`",
+            rowid, row.table, row.parent
+        ));
+    }
+
+    Ok(issues)
 }
@@ -1,8 +1,13 @@
 use std::{env, path::PathBuf, str::FromStr};
 
+use crate::config;
+
+/// The AI model configuration used by the `prompt` feature.
 pub struct AIModelEnv {
-    api_key: String,
-    model: String,
+    /// The API key used to authenticate with the model provider.
+    pub api_key: String,
+    /// The model identifier.
+    pub model: String,
 }
 
 /// Gets the AI model configuration from the environment variables.
@@ -22,26 +27,241 @@ pub fn ai_model_env() -> AIModelEnv {
     AIModelEnv { api_key, model }
 }
 
+/// Gets the home directory of the current user.
+///
+/// This function checks the `HOME`, `USERPROFILE` and `HOMEPATH` environment variables in
+/// that order, so it works on Windows too, and falls back to the current directory.
+pub fn tudu_home_dir() -> PathBuf {
+    home_dir_from(non_empty_env_var, env::current_dir().unwrap_or_default())
+}
+
+/// Gets the directory tudu keeps its data in.
+///
+/// This is `$XDG_DATA_HOME/tudu` when `XDG_DATA_HOME` is set. Otherwise it is
+/// `%LOCALAPPDATA%\tudu` on Windows, `~/Library/Application Support/tudu` on macOS and
+/// `~/.local/share/tudu` everywhere else.
+pub fn tudu_data_dir() -> PathBuf {
+    data_dir_from(non_empty_env_var, tudu_home_dir(), env::consts::OS)
+}
+
+// Reads an environment variable, treating an empty value like a missing one
+fn non_empty_env_var(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.is_empty())
+}
+
+// The first home directory variable that `lookup` finds, or `fallback`
+fn home_dir_from(lookup: impl Fn(&str) -> Option<String>, fallback: PathBuf) -> PathBuf {
+    ["HOME", "USERPROFILE", "HOMEPATH"]
+        .iter()
+        .find_map(|key| lookup(key))
+        .map(PathBuf::from)
+        .unwrap_or(fallback)
+}
+
+// The tudu data directory for `os`, looking variables up with `lookup`
+fn data_dir_from(lookup: impl Fn(&str) -> Option<String>, home: PathBuf, os: &str) -> PathBuf {
+    let data_home = match (lookup("XDG_DATA_HOME"), os) {
+        (Some(xdg), _) => PathBuf::from(xdg),
+        (None, "windows") => lookup("LOCALAPPDATA")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join("AppData").join("Local")),
+        (None, "macos") => home.join("Library").join("Application Support"),
+        (None, _) => home.join(".local").join("share"),
+    };
+    data_home.join("tudu")
+}
+
 /// Gets the database path from the environment variables.
 ///
 /// This function looks for the `TUDU_DATABASE_URL` environment variable.
-/// If the variable is not set, it defaults to `$HOME/tudu.db`.
+/// If the variable is not set, it falls back to `DATABASE_URL` in the global
+/// config and then defaults to `Documents/tudu.db` in the home directory.
 ///
 /// # Panics
 ///
-/// This function will panic if the `TUDU_DATABASE_URL` is not valid unicode,
-/// or if `TUDU_DATABASE_URL` is not set and the `HOME` environment variable
-/// is not set.
+/// This function will panic if the `TUDU_DATABASE_URL` is not valid unicode.
 pub fn database_url_env() -> PathBuf {
     match env::var("TUDU_DATABASE_URL") {
         Ok(var) => PathBuf::from_str(var.as_str())
             .expect("Failed to parse TUDU_DATABASE_URL env variable."),
         Err(err) => match err {
             env::VarError::NotPresent => {
-                let documents_dir = env::var("HOME").expect("HOME environment variable not set");
-                PathBuf::from(documents_dir).join("Documents/tudu.db")
+                if let Some(config_url) = config::get_global_config_value("DATABASE_URL") {
+                    return PathBuf::from(config_url);
+                }
+                tudu_home_dir().join("Documents").join("tudu.db")
             }
             env::VarError::NotUnicode(_) => panic!("TUDU_DATABASE_URL not valid unicode"),
         },
     }
 }
+
+/// Gets the name of the current tudu user from the environment variables.
+///
+/// This function looks for the `TUDU_USER` environment variable and returns
+/// `None` when it is not set.
+pub fn tudu_user_env() -> Option<String> {
+    env::var("TUDU_USER")
+        .ok()
+        .filter(|user| !user.trim().is_empty())
+}
+
+/// Gets the number of spaces used for each level of nested todos.
+///
+/// This function looks for the `TUDU_INDENT_SIZE` environment variable and
+/// defaults to `4` when it is not set or is not a valid number.
+pub fn indent_size_env() -> usize {
+    env::var("TUDU_INDENT_SIZE")
+        .ok()
+        .and_then(|size| size.trim().parse().ok())
+        .unwrap_or(4)
+}
+
+/// Gets the age in days after which a todo is considered stale.
+///
+/// This function looks for the `TUDU_STALE_DAYS` environment variable and
+/// defaults to `7` when it is not set or is not a valid number.
+pub fn stale_days_env() -> i64 {
+    env::var("TUDU_STALE_DAYS")
+        .ok()
+        .and_then(|days| days.trim().parse().ok())
+        .unwrap_or(7)
+}
+
+/// Gets the width in characters of project progress bars.
+///
+/// This function looks for the `TUDU_PROGRESS_WIDTH` environment variable and
+/// defaults to `20` when it is not set or is not a valid number.
+pub fn progress_width_env() -> usize {
+    env::var("TUDU_PROGRESS_WIDTH")
+        .ok()
+        .and_then(|width| width.trim().parse().ok())
+        .unwrap_or(20)
+}
+
+/// Checks whether times should be shown with the 24-hour clock.
+///
+/// This is true when the `TUDU_24H` environment variable is set to `1` or the
+/// config has `TIME_FORMAT=24h`.
+pub fn is_24_hour_clock() -> bool {
+    env::var("TUDU_24H").is_ok_and(|value| value == "1")
+        || crate::config::get_config_value("TIME_FORMAT").is_some_and(|value| value == "24h")
+}
+
+/// Checks whether confirmation prompts should be answered yes automatically.
+///
+/// This is true when the `TUDU_YES` environment variable is set to `1`.
+pub fn is_auto_confirm() -> bool {
+    env::var("TUDU_YES").is_ok_and(|yes| yes == "1")
+}
+
+/// Checks whether tudu is running under test.
+///
+/// This is true for `cargo test` builds or when the `TUDU_TEST` environment
+/// variable is set.
+pub fn is_test_environment() -> bool {
+    env::var("TUDU_TEST").is_ok() || cfg!(test)
+}
+
+/// Checks whether `.tudu` and global config lookups are turned off through `TUDU_IGNORE_CONFIG=1`.
+pub fn is_config_ignored() -> bool {
+    env::var("TUDU_IGNORE_CONFIG").is_ok_and(|value| value == "1")
+}
+
+/// Checks whether debug output is enabled through `TUDU_DEBUG=1`.
+pub fn is_debug_mode() -> bool {
+    env::var("TUDU_DEBUG").is_ok_and(|value| value == "1")
+}
+
+/// Gets the width of the terminal in characters.
+///
+/// This function looks for the `COLUMNS` environment variable and defaults
+/// to `120` when it is not set or is not a valid number.
+pub fn terminal_width_env() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .unwrap_or(120)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_home_dir_prefers_home() {
+        let lookup = lookup_from(&[("HOME", "/home/sam"), ("USERPROFILE", "C:\\Users\\sam")]);
+        assert_eq!(
+            home_dir_from(lookup, PathBuf::from("/cwd")),
+            PathBuf::from("/home/sam")
+        );
+    }
+
+    #[test]
+    fn test_home_dir_falls_back_to_userprofile() {
+        let lookup = lookup_from(&[
+            ("USERPROFILE", "C:\\Users\\sam"),
+            ("HOMEPATH", "\\Users\\sam"),
+        ]);
+        assert_eq!(
+            home_dir_from(lookup, PathBuf::from("/cwd")),
+            PathBuf::from("C:\\Users\\sam")
+        );
+    }
+
+    #[test]
+    fn test_home_dir_falls_back_to_homepath() {
+        let lookup = lookup_from(&[("HOMEPATH", "\\Users\\sam")]);
+        assert_eq!(
+            home_dir_from(lookup, PathBuf::from("/cwd")),
+            PathBuf::from("\\Users\\sam")
+        );
+    }
+
+    #[test]
+    fn test_home_dir_falls_back_to_current_dir() {
+        assert_eq!(
+            home_dir_from(lookup_from(&[]), PathBuf::from("/cwd")),
+            PathBuf::from("/cwd")
+        );
+    }
+
+    #[test]
+    fn test_data_dir_prefers_xdg_data_home() {
+        let lookup = lookup_from(&[("XDG_DATA_HOME", "/data")]);
+        assert_eq!(
+            data_dir_from(lookup, PathBuf::from("/home/sam"), "linux"),
+            PathBuf::from("/data/tudu")
+        );
+    }
+
+    #[test]
+    fn test_data_dir_per_os() {
+        let home = PathBuf::from("/home/sam");
+        assert_eq!(
+            data_dir_from(lookup_from(&[]), home.clone(), "linux"),
+            PathBuf::from("/home/sam/.local/share/tudu")
+        );
+        assert_eq!(
+            data_dir_from(lookup_from(&[]), home.clone(), "macos"),
+            PathBuf::from("/home/sam/Library/Application Support/tudu")
+        );
+        assert_eq!(
+            data_dir_from(
+                lookup_from(&[("LOCALAPPDATA", "/appdata")]),
+                home,
+                "windows"
+            ),
+            PathBuf::from("/appdata/tudu")
+        );
+    }
+}
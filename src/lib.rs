@@ -2,32 +2,60 @@ use clap::{Command, crate_description, crate_name, crate_version};
 
 use crate::{
     arg::TuduArg,
+    config::command::{init_command, set_config_command},
+    export::{export_csv_command, export_json_command, export_markdown_command},
     project::command::{
-        close_project_command, list_project_command, new_project_command, update_project_command,
-        view_project_command,
+        close_project_command, list_project_command, new_project_command, rename_project_command,
+        update_project_command, view_project_command,
     },
+    stats::stats_command,
     todo::command::{
-        close_todo_command, list_todo_command, new_todo_command, update_todo_command,
-        view_todo_command,
+        close_todo_command, delete_todo_command, duplicate_todo_command, list_todo_command,
+        move_todo_command, new_todo_command, rename_todo_command, reopen_todo_command,
+        search_todo_command, update_todo_command, view_todo_command,
     },
 };
 pub mod arg;
-mod config;
+pub mod config;
 pub mod display;
 pub mod error;
+pub mod export;
 pub mod infrastructure;
 pub mod project;
 mod schema;
+pub mod stats;
 pub mod todo;
 
+fn migrations_command() -> Command {
+    Command::new("migrations")
+        .about("Run any pending migrations to the database store. Create db if it doesnt exist")
+        .alias("migrate")
+        .arg(TuduArg::Check.into_arg(true))
+}
+
 pub fn cli() -> Command {
     Command::new(crate_name!())
         .about(crate_description!())
         .version(crate_version!())
         .arg(TuduArg::Prompt.into_arg(false))
-        .subcommand(Command::new("migrations").about(
-            "Run any pending migrations to the database store. Create db if it doesnt exist",
-        ))
+        .subcommand(migrations_command())
+        .subcommand(
+            Command::new("database")
+                .about("Maintain the database store")
+                .subcommand_required(true)
+                .subcommand(migrations_command())
+                .subcommand(
+                    Command::new("check")
+                        .about("Check the database for corruption and orphaned rows"),
+                ),
+        )
+        .subcommand(init_command())
+        .subcommand(
+            Command::new("config")
+                .about("Manage the `.tudu` and global config files")
+                .subcommand_required(true)
+                .subcommand(set_config_command()),
+        )
         .subcommand(
             Command::new("new")
                 .subcommand_required(true)
@@ -42,6 +70,13 @@ pub fn cli() -> Command {
                 .subcommand(update_todo_command())
                 .subcommand(update_project_command()),
         )
+        .subcommand(
+            Command::new("rename")
+                .about("Rename an existing instance of")
+                .subcommand_required(true)
+                .subcommand(rename_todo_command())
+                .subcommand(rename_project_command()),
+        )
         .subcommand(
             Command::new("close")
                 .about("Close an existing instance of")
@@ -49,6 +84,30 @@ pub fn cli() -> Command {
                 .subcommand(close_todo_command())
                 .subcommand(close_project_command()),
         )
+        .subcommand(
+            Command::new("reopen")
+                .about("Reopen a closed instance of")
+                .subcommand_required(true)
+                .subcommand(reopen_todo_command()),
+        )
+        .subcommand(
+            Command::new("duplicate")
+                .about("Copy an existing instance of into a new one")
+                .subcommand_required(true)
+                .subcommand(duplicate_todo_command()),
+        )
+        .subcommand(
+            Command::new("move")
+                .about("Move an existing instance of to another project")
+                .subcommand_required(true)
+                .subcommand(move_todo_command()),
+        )
+        .subcommand(
+            Command::new("delete")
+                .about("Permanently delete an existing instance of")
+                .subcommand_required(true)
+                .subcommand(delete_todo_command()),
+        )
         .subcommand(
             Command::new("view")
                 .about("View the details for a single instance of, optionally filtered")
@@ -63,4 +122,19 @@ pub fn cli() -> Command {
                 .subcommand(list_todo_command())
                 .subcommand(list_project_command()),
         )
+        .subcommand(
+            Command::new("export")
+                .about("Export data for other tools")
+                .subcommand_required(true)
+                .subcommand(export_json_command())
+                .subcommand(export_csv_command())
+                .subcommand(export_markdown_command()),
+        )
+        .subcommand(stats_command())
+        .subcommand(
+            Command::new("search")
+                .about("Search for items containing some text")
+                .subcommand_required(true)
+                .subcommand(search_todo_command()),
+        )
 }
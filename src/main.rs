@@ -1,48 +1,148 @@
+use diesel::SqliteConnection;
 use tudu::{
     arg::TuduArg,
     cli,
-    display::{error_message, migration_message},
+    config::command::{handle_init_command, handle_set_config_command},
+    display::{
+        database_check_message, error_message, migration_message, pending_migrations_message,
+    },
     error::TuduError,
+    export::{
+        handle_export_csv_command, handle_export_json_command, handle_export_markdown_command,
+    },
     infrastructure::database,
     project::command::{
         handle_close_project_command, handle_list_project_command, handle_new_project_command,
-        handle_update_project_command, handle_view_project_command,
+        handle_rename_project_command, handle_update_project_command, handle_view_project_command,
     },
+    stats::handle_stats_command,
     todo::command::{
-        handle_close_todo_command, handle_list_todo_command, handle_new_todo_command,
+        handle_close_todo_command, handle_delete_todo_command, handle_duplicate_todo_command,
+        handle_list_todo_command, handle_move_todo_command, handle_new_todo_command,
+        handle_rename_todo_command, handle_reopen_todo_command, handle_search_todo_command,
         handle_update_todo_command, handle_view_todo_command,
     },
 };
 
-struct CommandProcessor;
+struct CommandProcessor {
+    connection: Option<SqliteConnection>,
+}
 
 impl CommandProcessor {
     pub fn new() -> Self {
-        Self {}
+        Self { connection: None }
+    }
+
+    /// The connection shared by every command, opened the first time it is needed.
+    fn connection(&mut self) -> &mut SqliteConnection {
+        self.connection
+            .get_or_insert_with(database::database_connection)
+    }
+
+    fn handle_migrations_command(&mut self, cmd_matches: &clap::ArgMatches) {
+        if cmd_matches.get_flag(TuduArg::Check.name()) {
+            match database::list_pending_migrations(self.connection()) {
+                Ok(pending) => {
+                    pending_migrations_message(&pending);
+                    // A non-zero exit code lets CI fail when the schema is behind
+                    if !pending.is_empty() {
+                        std::process::exit(1);
+                    }
+                }
+                Err(err) => error_message(err),
+            }
+            return;
+        }
+
+        match database::run_database_migrations(self.connection()) {
+            Ok(migrations) => migration_message(migrations),
+            Err(err) => error_message(err),
+        }
     }
-    fn handle_migrations_command(&mut self) {
-        match database::run_database_migrations() {
-            Ok(migration_count) => migration_message(migration_count),
+
+    fn handle_database_check_command(&mut self) {
+        match database::check_database_integrity(self.connection()) {
+            Ok(issues) => database_check_message(issues),
             Err(err) => error_message(err),
         }
     }
 
+    fn process_database_subcommands(&mut self, cmd_matches: &clap::ArgMatches) {
+        match cmd_matches.subcommand() {
+            Some(("migrations", migrations_matches)) => {
+                self.handle_migrations_command(migrations_matches)
+            }
+            Some(("check", _)) => self.handle_database_check_command(),
+            Some((_, _)) => error_message(TuduError::CommandNotFoundError),
+            None => error_message(TuduError::CommandRequiredError),
+        }
+    }
+
+    fn process_config_subcommands(&mut self, cmd_matches: &clap::ArgMatches) {
+        let result = match cmd_matches.subcommand() {
+            Some(("set", set_matches)) => handle_set_config_command(set_matches),
+            Some((_, _)) => Err(TuduError::CommandNotFoundError),
+            None => Err(TuduError::CommandRequiredError),
+        };
+
+        if let Err(error) = result {
+            error_message(error)
+        }
+    }
+
+    fn handle_init_command(&mut self, cmd_matches: &clap::ArgMatches) {
+        if let Err(error) = handle_init_command(cmd_matches, self.connection()) {
+            error_message(error)
+        }
+    }
+
+    fn process_export_subcommands(&mut self, cmd_matches: &clap::ArgMatches) {
+        let result = match cmd_matches.subcommand() {
+            Some(("json", json_matches)) => {
+                handle_export_json_command(json_matches, self.connection())
+            }
+            Some(("csv", csv_matches)) => handle_export_csv_command(csv_matches, self.connection()),
+            Some(("markdown", markdown_matches)) => {
+                handle_export_markdown_command(markdown_matches, self.connection())
+            }
+            Some((_, _)) => Err(TuduError::CommandNotFoundError),
+            None => Err(TuduError::CommandRequiredError),
+        };
+
+        if let Err(error) = result {
+            error_message(error)
+        }
+    }
+
+    fn handle_stats_command(&mut self, cmd_matches: &clap::ArgMatches) {
+        if let Err(error) = handle_stats_command(cmd_matches, self.connection()) {
+            error_message(error)
+        }
+    }
+
     fn process_subcommands(&mut self, cmd_matches: &clap::ArgMatches, action: &str) {
         let result = match cmd_matches.subcommand() {
             Some(("todo", todo_matches)) => match action {
-                "new" => handle_new_todo_command(todo_matches),
-                "update" => handle_update_todo_command(todo_matches),
-                "close" => handle_close_todo_command(todo_matches),
-                "view" => handle_view_todo_command(todo_matches),
-                "list" => handle_list_todo_command(todo_matches),
+                "new" => handle_new_todo_command(todo_matches, self.connection()),
+                "update" => handle_update_todo_command(todo_matches, self.connection()),
+                "rename" => handle_rename_todo_command(todo_matches, self.connection()),
+                "close" => handle_close_todo_command(todo_matches, self.connection()),
+                "reopen" => handle_reopen_todo_command(todo_matches, self.connection()),
+                "duplicate" => handle_duplicate_todo_command(todo_matches, self.connection()),
+                "move" => handle_move_todo_command(todo_matches, self.connection()),
+                "delete" => handle_delete_todo_command(todo_matches, self.connection()),
+                "view" => handle_view_todo_command(todo_matches, self.connection()),
+                "list" => handle_list_todo_command(todo_matches, self.connection()),
+                "search" => handle_search_todo_command(todo_matches, self.connection()),
                 _ => unreachable!(),
             },
             Some(("project", project_matches)) => match action {
-                "new" => handle_new_project_command(project_matches),
-                "update" => handle_update_project_command(project_matches),
-                "close" => handle_close_project_command(project_matches),
-                "view" => handle_view_project_command(project_matches),
-                "list" => handle_list_project_command(),
+                "new" => handle_new_project_command(project_matches, self.connection()),
+                "update" => handle_update_project_command(project_matches, self.connection()),
+                "rename" => handle_rename_project_command(project_matches, self.connection()),
+                "close" => handle_close_project_command(project_matches, self.connection()),
+                "view" => handle_view_project_command(project_matches, self.connection()),
+                "list" => handle_list_project_command(project_matches, self.connection()),
                 _ => unreachable!(),
             },
             Some((_, _)) => Err(TuduError::CommandNotFoundError),
@@ -64,15 +164,34 @@ fn main() {
         return;
     }
 
+    if let Some((name, _)) = m.subcommand()
+        && !matches!(name, "migrations" | "config")
+        && let Err(e) = database::ensure_database_exists()
+    {
+        error_message(e);
+        return;
+    }
+
     let mut processor = CommandProcessor::new();
 
     match m.subcommand() {
-        Some(("migrations", _)) => processor.handle_migrations_command(),
+        Some(("migrations", cmd_matches)) => processor.handle_migrations_command(cmd_matches),
+        Some(("database", cmd_matches)) => processor.process_database_subcommands(cmd_matches),
+        Some(("init", cmd_matches)) => processor.handle_init_command(cmd_matches),
+        Some(("config", cmd_matches)) => processor.process_config_subcommands(cmd_matches),
         Some(("new", cmd_matches)) => processor.process_subcommands(cmd_matches, "new"),
         Some(("update", cmd_matches)) => processor.process_subcommands(cmd_matches, "update"),
+        Some(("rename", cmd_matches)) => processor.process_subcommands(cmd_matches, "rename"),
         Some(("close", cmd_matches)) => processor.process_subcommands(cmd_matches, "close"),
+        Some(("reopen", cmd_matches)) => processor.process_subcommands(cmd_matches, "reopen"),
+        Some(("duplicate", cmd_matches)) => processor.process_subcommands(cmd_matches, "duplicate"),
+        Some(("move", cmd_matches)) => processor.process_subcommands(cmd_matches, "move"),
+        Some(("delete", cmd_matches)) => processor.process_subcommands(cmd_matches, "delete"),
         Some(("view", cmd_matches)) => processor.process_subcommands(cmd_matches, "view"),
         Some(("list", cmd_matches)) => processor.process_subcommands(cmd_matches, "list"),
+        Some(("search", cmd_matches)) => processor.process_subcommands(cmd_matches, "search"),
+        Some(("export", cmd_matches)) => processor.process_export_subcommands(cmd_matches),
+        Some(("stats", cmd_matches)) => processor.handle_stats_command(cmd_matches),
         Some((_, _)) => error_message(TuduError::CommandNotFoundError),
         None => error_message(TuduError::CommandRequiredError),
     }
@@ -1,89 +1,222 @@
 use crate::{
-    arg::{TuduArg, ValidHexColor, parse_required_project_id},
-    display::{Display, Prefix},
+    arg::{
+        OutputFormat, ProjectId, TuduArg, ValidDateTime, ValidHexColor, get_negatable_flag,
+        parse_required_project_id,
+    },
+    config::{parse_tudu_file, write_project_id_to_config},
+    display::{
+        Display, Prefix, color_change_message, info_message, output_writer, output_written_message,
+        print_table, project_counts_table, project_id_source_message, todo_status_bar,
+        warning_message,
+    },
     error::{TuduError, TuduResult},
-    infrastructure::database,
-    project::sql::{NewProject, Project, UpdateProject},
+    project::{
+        query::ProjectQuery,
+        sql::{ArchiveProject, NewProject, PALETTE, Project, ProjectStatus, UpdateProject},
+        summary::{ProjectActivity, ProjectProgress, ProjectSummary},
+    },
     schema::projects::dsl as projects_dsl,
     todo::{
-        group::organize_todos_hierarchically,
+        group::{TodoGroup, organize_todos_hierarchically},
         sql::{Todo, TodoStatus},
     },
 };
 use clap::{ArgMatches, Command};
 use diesel::{
-    Connection, ExpressionMethods, QueryDsl, RunQueryDsl,
-    dsl::{delete, insert_into},
+    Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection,
+    dsl::{count, exists, insert_into, not},
     update,
 };
+use std::{collections::HashMap, io::Write, path::PathBuf};
 
 pub fn new_project_command() -> Command {
     Command::new("project").args([
         TuduArg::Name.into_arg(false).required(true),
         TuduArg::Description.into_arg(true),
         TuduArg::Color.into_arg(true),
+        TuduArg::InitConfig.into_arg(true),
+        TuduArg::Force
+            .into_arg(true)
+            .requires(TuduArg::InitConfig.name()),
     ])
 }
 
 fn parse_new_project_command_matches(matches: &ArgMatches) -> TuduResult<NewProject> {
     let name: &String = matches
         .get_one(TuduArg::Name.name())
-        .ok_or_else(|| TuduError::RequiredArgumentError)?;
+        .ok_or(TuduError::RequiredArgumentError)?;
     let description: Option<&String> = matches.get_one(TuduArg::Description.name());
     let color: Option<&ValidHexColor> = matches.get_one(TuduArg::Color.name());
 
     Ok(NewProject {
         name: name.clone(),
-        description: description.map(|desc| desc.clone()),
+        description: description.cloned(),
         color: color.map(|c| c.0.clone()),
     })
 }
 
-pub fn handle_new_project_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
+pub fn handle_new_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
     let new_project = parse_new_project_command_matches(matches)?;
 
-    let res = connection.transaction(move |conn| {
+    let res = conn.transaction(move |conn| {
         insert_into(projects_dsl::projects)
             .values(new_project)
             .get_result::<Project>(conn)
     })?;
 
     res.to_message(Some(Prefix::New)).display();
+
+    if matches.get_flag(TuduArg::InitConfig.name()) {
+        init_project_config(res.id, matches.get_flag(TuduArg::Force.name()))?;
+    }
+    Ok(())
+}
+
+// Writes the project id to ./.tudu, leaving a different existing PROJECT_ID alone unless forced
+fn init_project_config(project_id: i32, force: bool) -> TuduResult<()> {
+    let config_path = std::env::current_dir()?.join(".tudu");
+    let current = parse_tudu_file(config_path.clone())
+        .ok()
+        .and_then(|mut values| values.remove("PROJECT_ID"));
+
+    if let Some(current) = current.filter(|value| !force && *value != project_id.to_string()) {
+        warning_message(
+            &format!(".tudu already has PROJECT_ID={}", current),
+            "Use --force to overwrite it.",
+        );
+        return Ok(());
+    }
+
+    write_project_id_to_config(project_id)?;
+    info_message(&format!("Initialized .tudu with PROJECT_ID={}", project_id));
     Ok(())
 }
 
+// The palette color after `current`, or the first one when `current` isn't in the palette
+fn next_palette_color(current: Option<&str>) -> &'static str {
+    let position = current.and_then(|color| {
+        PALETTE
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(color))
+    });
+    match position {
+        Some(index) => PALETTE[(index + 1) % PALETTE.len()],
+        None => PALETTE[0],
+    }
+}
+
 pub fn update_project_command() -> Command {
     Command::new("project").args([
         TuduArg::ProjectId.into_arg(false),
         TuduArg::Name.into_arg(true),
         TuduArg::Description.into_arg(true),
-        TuduArg::Color.into_arg(true),
+        TuduArg::Color
+            .into_arg(true)
+            .conflicts_with(TuduArg::NextColor.name()),
+        TuduArg::NextColor.into_arg(true),
+        TuduArg::ProjectStatus.into_arg(true),
     ])
 }
 
-fn parse_update_project_command_matches(matches: &ArgMatches) -> TuduResult<UpdateProject> {
-    let id = parse_required_project_id(matches)?;
+fn parse_update_project_command_matches(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<UpdateProject> {
+    let (id, source) = parse_required_project_id(matches, conn)?;
+    project_id_source_message(id, &source);
     let name: Option<&String> = matches.get_one(TuduArg::Name.name());
     let description: Option<&String> = matches.get_one(TuduArg::Description.name());
     let color: Option<&ValidHexColor> = matches.get_one(TuduArg::Color.name());
+    let status: Option<&ProjectStatus> = matches.get_one(TuduArg::ProjectStatus.name());
 
     Ok(UpdateProject {
         id,
-        name: name.map(|name| name.clone()),
-        description: description.map(|desc| desc.clone()),
+        name: name.cloned(),
+        description: description.cloned(),
         color: color.map(|c| c.0.clone()),
         updated_at: chrono::Utc::now().naive_utc(),
+        status: status.copied(),
+        archived_at: status.map(|status| match status {
+            ProjectStatus::Archived => Some(chrono::Utc::now().naive_utc()),
+            _ => None,
+        }),
     })
 }
 
-pub fn handle_update_project_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
-    let update_project = parse_update_project_command_matches(matches)?;
+pub fn handle_update_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let mut update_project = parse_update_project_command_matches(matches, conn)?;
+    let next_color = matches.get_flag(TuduArg::NextColor.name());
+
+    let (old_color, res) = conn.transaction(
+        move |conn| -> Result<(Option<String>, Project), diesel::result::Error> {
+            // Inactive projects can still be updated, e.g. to make them active again
+            let old_color = ProjectQuery::new()
+                .include_inactive()
+                .build()
+                .filter(projects_dsl::id.eq(update_project.id))
+                .select(projects_dsl::color)
+                .first::<Option<String>>(conn)?;
+            if next_color {
+                update_project.color = Some(next_palette_color(old_color.as_deref()).to_string());
+            }
+            let project =
+                update(projects_dsl::projects.filter(projects_dsl::id.eq(update_project.id)))
+                    .set(update_project)
+                    .get_result::<Project>(conn)?;
+            Ok((old_color, project))
+        },
+    )?;
 
-    let res = connection.transaction(move |conn| {
-        update(projects_dsl::projects.filter(projects_dsl::id.eq(update_project.id)))
-            .set(update_project)
+    res.to_message(Some(Prefix::Update)).display();
+    if next_color && let Some(new_color) = res.color.clone() {
+        color_change_message(old_color, new_color);
+    }
+    Ok(())
+}
+
+pub fn rename_project_command() -> Command {
+    Command::new("project").args([
+        TuduArg::ProjectId.into_arg(false),
+        TuduArg::Name.into_arg(true).required(true),
+    ])
+}
+
+fn parse_rename_project_command_matches(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<UpdateProject> {
+    let (id, source) = parse_required_project_id(matches, conn)?;
+    project_id_source_message(id, &source);
+    let name: &String = matches
+        .get_one(TuduArg::Name.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+
+    Ok(UpdateProject {
+        id,
+        name: Some(name.clone()),
+        description: None,
+        color: None,
+        updated_at: chrono::Utc::now().naive_utc(),
+        status: None,
+        archived_at: None,
+    })
+}
+
+pub fn handle_rename_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let rename_project = parse_rename_project_command_matches(matches, conn)?;
+
+    let res = conn.transaction(move |conn| {
+        update(projects_dsl::projects.filter(projects_dsl::id.eq(rename_project.id)))
+            .set(rename_project)
             .get_result::<Project>(conn)
     })?;
 
@@ -92,83 +225,529 @@ pub fn handle_update_project_command(matches: &ArgMatches) -> TuduResult<()> {
 }
 
 pub fn close_project_command() -> Command {
-    Command::new("project").args([TuduArg::ProjectId.into_arg(false)])
+    Command::new("project").args([
+        TuduArg::ProjectId.into_arg(false),
+        // Archiving can be undone so it never asks, but scripts written for the old prompt still
+        // pass --yes
+        TuduArg::Yes.into_arg(true).hide(true),
+    ])
 }
 
-fn parse_close_project_command_matches(matches: &ArgMatches) -> TuduResult<i32> {
-    parse_required_project_id(matches)
+fn parse_close_project_command_matches(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<ProjectId> {
+    let (id, source) = parse_required_project_id(matches, conn)?;
+    project_id_source_message(id, &source);
+    Ok(id)
 }
 
-pub fn handle_close_project_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
-    let close_project = parse_close_project_command_matches(matches)?;
+pub fn handle_close_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let close_project = parse_close_project_command_matches(matches, conn)?;
 
-    let res = connection.transaction(move |conn| {
-        delete(projects_dsl::projects.filter(projects_dsl::id.eq(close_project))).execute(conn)
+    // Closing archives the project and keeps its todos, so unlike deleting it can be undone
+    let res = conn.transaction(move |conn| {
+        update(projects_dsl::projects.filter(projects_dsl::id.eq(close_project)))
+            .set(ArchiveProject::new(close_project))
+            .get_result::<Project>(conn)
     })?;
 
-    crate::display::simple_heading(
-        format!("Deleted {}: Project {}", res, close_project),
-        Some("#ff0000".to_string()),
-    );
+    res.to_message(Some(Prefix::Close)).display();
+    info_message(&format!(
+        "Archived project #{}. Restore it with `tudu update project {} --project_status active`",
+        res.id, res.id
+    ));
     Ok(())
 }
 
 pub fn view_project_command() -> Command {
-    Command::new("project").args([TuduArg::ProjectId.into_arg(false)])
+    Command::new("project")
+        .args([
+            TuduArg::ProjectId.into_arg(false),
+            TuduArg::Output.into_arg(true),
+            TuduArg::Format.into_arg(true),
+            TuduArg::DebugDisplay
+                .into_arg(true)
+                .conflicts_with(TuduArg::Format.name()),
+        ])
+        .args(TuduArg::IncludeDone.into_negatable_args(true))
 }
 
-pub fn parse_view_project_command_matches(matches: &ArgMatches) -> TuduResult<i32> {
-    parse_required_project_id(matches)
+pub fn parse_view_project_command_matches(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<ProjectId> {
+    let (id, source) = parse_required_project_id(matches, conn)?;
+    project_id_source_message(id, &source);
+    Ok(id)
 }
 
-pub fn handle_view_project_command(matches: &ArgMatches) -> TuduResult<()> {
+// Moves done groups, and done subtodos within each group, after the others, keeping their order
+fn done_todos_last(todo_groups: &mut [TodoGroup]) {
+    let is_done = |todo: &Todo| todo.status == TodoStatus::Done;
+    todo_groups.sort_by_key(|group| is_done(&group.main_todo));
+    for group in todo_groups.iter_mut() {
+        group.subtodos.sort_by_key(is_done);
+    }
+}
+
+pub fn handle_view_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
     use crate::schema::todos::dsl as todos_dsl;
-    let mut connection = database::database_connection();
-    let view_project_id = parse_view_project_command_matches(matches)?;
+    let view_project_id = parse_view_project_command_matches(matches, conn)?;
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let format = matches
+        .get_one::<OutputFormat>(TuduArg::Format.name())
+        .copied()
+        .unwrap_or_default();
+    let debug_display = matches.get_flag(TuduArg::DebugDisplay.name());
+    let include_done = get_negatable_flag(matches, TuduArg::IncludeDone, false);
 
-    let (project, todos) = connection.transaction(
+    let (project, todos) = conn.transaction(
         move |conn| -> Result<(Project, Vec<Todo>), diesel::result::Error> {
-            let project = projects_dsl::projects
+            let project = ProjectQuery::new()
+                .build()
                 .filter(projects_dsl::id.eq(view_project_id))
                 .first::<Project>(conn)?;
-            let todos = todos_dsl::todos
+            // Only top-level todos and their direct children are displayed, so load the
+            // top-level todos first and then all of their subtodos in one batch query.
+            // This keeps the view at two todo queries regardless of project size.
+            let mut top_level_query = todos_dsl::todos
                 .filter(todos_dsl::project_id.eq(view_project_id))
-                .filter(todos_dsl::status.ne(TodoStatus::Done))
-                .load::<Todo>(conn)?;
+                .filter(todos_dsl::parent_id.is_null())
+                .into_boxed();
+            if !include_done {
+                top_level_query = top_level_query.filter(todos_dsl::status.ne(TodoStatus::Done));
+            }
+            let mut todos = top_level_query.load::<Todo>(conn)?;
+            let parent_ids: Vec<i32> = todos.iter().map(|todo| todo.id).collect();
+            let mut subtodo_query = todos_dsl::todos
+                .filter(todos_dsl::parent_id.eq_any(parent_ids))
+                .into_boxed();
+            if !include_done {
+                subtodo_query = subtodo_query.filter(todos_dsl::status.ne(TodoStatus::Done));
+            }
+            let subtodos = subtodo_query.load::<Todo>(conn)?;
+            todos.extend(subtodos);
             Ok((project, todos))
         },
     )?;
 
-    project.to_message(None).display();
+    let mut writer = output_writer(output)?;
+    if format == OutputFormat::Minimal {
+        writeln!(writer, "{}", project.to_oneliner(todos.len()))?;
+        if let Some(path) = output {
+            output_written_message(path);
+        }
+        return Ok(());
+    }
 
-    // Organize todos hierarchically and display them
-    let todo_groups = organize_todos_hierarchically(todos);
+    if debug_display {
+        project.to_debug_message().display_to_writer(&mut writer)?;
+        for todo in &todos {
+            todo.to_debug_message().display_to_writer(&mut writer)?;
+        }
+        if let Some(path) = output {
+            output_written_message(path);
+        }
+        return Ok(());
+    }
+
+    project.to_message(None).display_to_writer(&mut writer)?;
+
+    match todo_status_bar(&todos) {
+        Some(status_bar) => writeln!(writer, "{}", status_bar)?,
+        None => writeln!(
+            writer,
+            "No todos yet. Add one with: tudu new todo \"...\" {}",
+            project.id
+        )?,
+    }
+
+    // Organize todos hierarchically and display them, with finished work after the open todos
+    let mut todo_groups = organize_todos_hierarchically(todos);
+    if include_done {
+        done_todos_last(&mut todo_groups);
+    }
     for group in todo_groups {
-        println!();
-        group.to_message(None).display();
+        writeln!(writer)?;
+        group.to_message(None).display_to_writer(&mut writer)?;
     }
 
+    if let Some(path) = output {
+        output_written_message(path);
+    }
     Ok(())
 }
 
 pub fn list_project_command() -> Command {
-    Command::new("project")
+    Command::new("project").args([
+        TuduArg::HasOverdue
+            .into_arg(true)
+            .conflicts_with(TuduArg::NoOverdue.name()),
+        TuduArg::NoOverdue.into_arg(true),
+        TuduArg::Output.into_arg(true),
+        TuduArg::WithLastActivity.into_arg(true),
+        TuduArg::WithProgress.into_arg(true),
+        TuduArg::WithTodoCounts.into_arg(true),
+        TuduArg::CreatedAfter.into_arg(true),
+        TuduArg::CreatedBefore.into_arg(true),
+        TuduArg::UpdatedAfter.into_arg(true),
+        TuduArg::UpdatedBefore.into_arg(true),
+        TuduArg::IncludeArchived.into_arg(true),
+    ])
 }
 
-pub fn handle_list_project_command() -> TuduResult<()> {
-    let mut connection = database::database_connection();
+struct ListProjectFilters {
+    has_overdue: bool,
+    no_overdue: bool,
+    output: Option<PathBuf>,
+    with_last_activity: bool,
+    with_progress: bool,
+    with_todo_counts: bool,
+    created_after: Option<chrono::NaiveDateTime>,
+    created_before: Option<chrono::NaiveDateTime>,
+    updated_after: Option<chrono::NaiveDateTime>,
+    updated_before: Option<chrono::NaiveDateTime>,
+    include_archived: bool,
+}
 
-    let res: Vec<Project> = connection.transaction(move |conn| {
-        projects_dsl::projects
-            .order(projects_dsl::created_at.desc())
-            .load::<Project>(conn)
-    })?;
+fn parse_list_project_command_matches(matches: &ArgMatches) -> TuduResult<ListProjectFilters> {
+    let has_overdue = matches.get_flag(TuduArg::HasOverdue.name());
+    let no_overdue = matches.get_flag(TuduArg::NoOverdue.name());
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let with_last_activity = matches.get_flag(TuduArg::WithLastActivity.name());
+    let with_progress = matches.get_flag(TuduArg::WithProgress.name());
+    let with_todo_counts = matches.get_flag(TuduArg::WithTodoCounts.name());
+    let date = |arg: TuduArg| matches.get_one::<ValidDateTime>(arg.name()).map(|d| d.0);
+    Ok(ListProjectFilters {
+        has_overdue,
+        no_overdue,
+        output: output.cloned(),
+        with_last_activity,
+        with_progress,
+        with_todo_counts,
+        created_after: date(TuduArg::CreatedAfter),
+        created_before: date(TuduArg::CreatedBefore),
+        updated_after: date(TuduArg::UpdatedAfter),
+        updated_before: date(TuduArg::UpdatedBefore),
+        include_archived: matches.get_flag(TuduArg::IncludeArchived.name()),
+    })
+}
+
+pub fn handle_list_project_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    use crate::schema::todos::dsl as todos_dsl;
+
+    let filters = parse_list_project_command_matches(matches)?;
+    let now = chrono::Utc::now().naive_utc();
+
+    let res: Vec<ProjectSummary> = conn.transaction(
+        |conn| -> Result<Vec<ProjectSummary>, diesel::result::Error> {
+            let overdue_todos = todos_dsl::todos
+                .filter(todos_dsl::project_id.eq(projects_dsl::id))
+                .filter(todos_dsl::due_date.lt(now))
+                .filter(todos_dsl::status.ne_all([TodoStatus::Done, TodoStatus::Cancelled]));
+
+            let project_query = if filters.include_archived {
+                ProjectQuery::new().include_archived()
+            } else {
+                ProjectQuery::new()
+            };
+            let mut query = project_query.build().order(projects_dsl::created_at.desc());
+            if filters.has_overdue {
+                query = query.filter(exists(overdue_todos));
+            } else if filters.no_overdue {
+                query = query.filter(not(exists(overdue_todos)));
+            }
+            // Comparing against a null timestamp is never true, so projects without the
+            // timestamp are left out whenever its filter is used
+            if let Some(date) = filters.created_after {
+                query = query.filter(projects_dsl::created_at.gt(date));
+            }
+            if let Some(date) = filters.created_before {
+                query = query.filter(projects_dsl::created_at.lt(date));
+            }
+            if let Some(date) = filters.updated_after {
+                query = query.filter(projects_dsl::updated_at.gt(date));
+            }
+            if let Some(date) = filters.updated_before {
+                query = query.filter(projects_dsl::updated_at.lt(date));
+            }
+            let projects = query.load::<Project>(conn)?;
+            let mut summaries: Vec<ProjectSummary> =
+                projects.into_iter().map(ProjectSummary::new).collect();
+
+            if filters.has_overdue {
+                let overdue_counts: HashMap<i32, i64> = todos_dsl::todos
+                    .filter(todos_dsl::due_date.lt(now))
+                    .filter(todos_dsl::status.ne_all([TodoStatus::Done, TodoStatus::Cancelled]))
+                    .group_by(todos_dsl::project_id)
+                    .select((todos_dsl::project_id, count(todos_dsl::id)))
+                    .load::<(i32, i64)>(conn)?
+                    .into_iter()
+                    .collect();
 
-    for project in res {
-        println!();
-        project.to_detailed_message(None).display();
+                summaries = summaries
+                    .into_iter()
+                    .map(|summary| {
+                        let overdue = overdue_counts.get(&summary.project.id).copied();
+                        summary.with_overdue_count(overdue.unwrap_or(0))
+                    })
+                    .collect();
+            }
+
+            if filters.with_last_activity {
+                let mut activities = load_project_activities(conn)?;
+                summaries = summaries
+                    .into_iter()
+                    .map(|summary| {
+                        let activity =
+                            activities
+                                .remove(&summary.project.id)
+                                .unwrap_or(ProjectActivity {
+                                    project_id: summary.project.id,
+                                    last_todo_title: None,
+                                    last_updated_at: None,
+                                });
+                        summary.with_last_activity(activity)
+                    })
+                    .collect();
+            }
+
+            if filters.with_progress || filters.with_todo_counts {
+                let status_counts = load_project_status_counts(conn)?;
+                summaries = summaries
+                    .into_iter()
+                    .map(|summary| {
+                        let counts = status_counts
+                            .get(&summary.project.id)
+                            .cloned()
+                            .unwrap_or_default();
+                        let summary = if filters.with_progress {
+                            summary.with_progress(ProjectProgress::from_counts(&counts))
+                        } else {
+                            summary
+                        };
+                        if filters.with_todo_counts {
+                            summary.with_todo_counts(counts)
+                        } else {
+                            summary
+                        }
+                    })
+                    .collect();
+            }
+
+            Ok(summaries)
+        },
+    )?;
+
+    // Only create the output file once the projects loaded, so a failure leaves it untouched
+    let mut writer = output_writer(filters.output.as_ref())?;
+    if filters.with_todo_counts {
+        let (headers, rows) = project_counts_table(&res);
+        print_table(&mut writer, headers, rows)?;
+    } else {
+        for summary in res {
+            writeln!(writer)?;
+            summary
+                .to_detailed_message(None)
+                .display_to_writer(&mut writer)?;
+        }
     }
 
+    if let Some(path) = &filters.output {
+        output_written_message(path);
+    }
     Ok(())
 }
+
+// The number of todos in each status for every project that has todos, keyed by project id
+fn load_project_status_counts(
+    conn: &mut SqliteConnection,
+) -> Result<HashMap<i32, HashMap<TodoStatus, usize>>, diesel::result::Error> {
+    use crate::schema::todos::dsl as todos_dsl;
+
+    let counts: Vec<(i32, TodoStatus, i64)> = todos_dsl::todos
+        .group_by((todos_dsl::project_id, todos_dsl::status))
+        .select((
+            todos_dsl::project_id,
+            todos_dsl::status,
+            count(todos_dsl::id),
+        ))
+        .load(conn)?;
+
+    let mut status_counts: HashMap<i32, HashMap<TodoStatus, usize>> = HashMap::new();
+    for (project_id, status, count) in counts {
+        status_counts
+            .entry(project_id)
+            .or_default()
+            .insert(status, count as usize);
+    }
+    Ok(status_counts)
+}
+
+// The most recently updated todo of every project that has todos, keyed by project id
+fn load_project_activities(
+    conn: &mut SqliteConnection,
+) -> Result<HashMap<i32, ProjectActivity>, diesel::result::Error> {
+    use crate::schema::todos::dsl as todos_dsl;
+
+    let latest: Vec<(i32, Option<chrono::NaiveDateTime>)> = todos_dsl::todos
+        .group_by(todos_dsl::project_id)
+        .select((
+            todos_dsl::project_id,
+            diesel::dsl::max(todos_dsl::updated_at),
+        ))
+        .load(conn)?;
+
+    let latest_times: Vec<chrono::NaiveDateTime> = latest
+        .iter()
+        .filter_map(|(_, updated_at)| *updated_at)
+        .collect();
+    let candidates: Vec<(i32, String, Option<chrono::NaiveDateTime>)> = todos_dsl::todos
+        .filter(todos_dsl::updated_at.eq_any(latest_times))
+        .select((
+            todos_dsl::project_id,
+            todos_dsl::title,
+            todos_dsl::updated_at,
+        ))
+        .load(conn)?;
+
+    Ok(latest
+        .into_iter()
+        .map(|(project_id, last_updated_at)| {
+            let last_todo_title = candidates
+                .iter()
+                .find(|(id, _, updated_at)| *id == project_id && *updated_at == last_updated_at)
+                .map(|(_, title, _)| title.clone());
+            (
+                project_id,
+                ProjectActivity {
+                    project_id,
+                    last_todo_title,
+                    last_updated_at,
+                },
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_palette_color() {
+        assert_eq!(next_palette_color(Some(PALETTE[0])), PALETTE[1]);
+        assert_eq!(next_palette_color(Some("#0d6efd")), PALETTE[1]);
+    }
+
+    #[test]
+    fn test_next_palette_color_wraps_around() {
+        assert_eq!(next_palette_color(Some(PALETTE[11])), PALETTE[0]);
+    }
+
+    #[test]
+    fn test_next_palette_color_unknown_or_missing() {
+        assert_eq!(next_palette_color(None), PALETTE[0]);
+        assert_eq!(next_palette_color(Some("#123456")), PALETTE[0]);
+    }
+
+    #[test]
+    fn test_close_project_archives_and_keeps_todos() {
+        use crate::{
+            infrastructure::database::{
+                in_memory_connection, insert_test_project, insert_test_todo, test_new_todo,
+            },
+            schema::todos::dsl as todos_dsl,
+        };
+
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Old");
+        insert_test_todo(&mut conn, test_new_todo(project.id, "Kept"));
+        let matches = close_project_command()
+            .try_get_matches_from(["project", project.id.to_string().as_str(), "--yes"])
+            .unwrap();
+
+        handle_close_project_command(&matches, &mut conn).unwrap();
+
+        let active = ProjectQuery::new()
+            .build()
+            .load::<Project>(&mut conn)
+            .unwrap();
+        assert!(active.is_empty());
+        let archived = ProjectQuery::new()
+            .include_archived()
+            .build()
+            .first::<Project>(&mut conn)
+            .unwrap();
+        assert_eq!(archived.status, ProjectStatus::Archived);
+        assert!(archived.archived_at.is_some());
+        let todos: i64 = todos_dsl::todos.count().get_result(&mut conn).unwrap();
+        assert_eq!(todos, 1);
+    }
+
+    fn create_test_todo(
+        id: i32,
+        parent_id: Option<i32>,
+        title: &str,
+        status: TodoStatus,
+        priority: crate::todo::sql::TodoPriority,
+    ) -> Todo {
+        Todo {
+            id,
+            project_id: 1,
+            parent_id,
+            title: title.to_string(),
+            description: None,
+            status,
+            priority,
+            due_date: None,
+            estimated_minutes: None,
+            location: None,
+            url: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            assignee: None,
+            blocked_by_id: None,
+            tags: None,
+            actual_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_done_todos_last() {
+        use crate::todo::sql::TodoPriority;
+
+        let todos = vec![
+            create_test_todo(1, None, "Shipped", TodoStatus::Done, TodoPriority::Urgent),
+            create_test_todo(2, None, "Open", TodoStatus::ToDo, TodoPriority::Low),
+            create_test_todo(3, Some(2), "Finished", TodoStatus::Done, TodoPriority::High),
+            create_test_todo(4, Some(2), "Pending", TodoStatus::ToDo, TodoPriority::Low),
+        ];
+
+        let mut todo_groups = organize_todos_hierarchically(todos);
+        done_todos_last(&mut todo_groups);
+
+        let titles: Vec<&str> = todo_groups
+            .iter()
+            .map(|group| group.main_todo.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Open", "Shipped"]);
+        let subtodo_titles: Vec<&str> = todo_groups[0]
+            .subtodos
+            .iter()
+            .map(|todo| todo.title.as_str())
+            .collect();
+        assert_eq!(subtodo_titles, ["Pending", "Finished"]);
+    }
+}
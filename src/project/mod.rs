@@ -1,2 +1,4 @@
 pub mod command;
+pub mod query;
 pub mod sql;
+pub mod summary;
@@ -0,0 +1,45 @@
+use crate::{
+    project::sql::ProjectStatus,
+    schema::projects::{self, dsl as projects_dsl},
+};
+use diesel::{ExpressionMethods, QueryDsl, sqlite::Sqlite};
+
+/// Builds queries over projects that only include active projects unless asked otherwise.
+#[derive(Debug, Default)]
+pub struct ProjectQuery {
+    include_inactive: bool,
+    include_archived: bool,
+}
+
+impl ProjectQuery {
+    /// Creates a query over active projects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Includes archived and paused projects as well.
+    pub fn include_inactive(mut self) -> Self {
+        self.include_inactive = true;
+        self
+    }
+
+    /// Includes archived projects as well, but still not paused ones.
+    pub fn include_archived(mut self) -> Self {
+        self.include_archived = true;
+        self
+    }
+
+    /// Builds the boxed query so callers can add their own filters and ordering.
+    pub fn build<'a>(self) -> projects::BoxedQuery<'a, Sqlite> {
+        let query = projects_dsl::projects.into_boxed();
+        if self.include_inactive {
+            query
+        } else if self.include_archived {
+            query.filter(
+                projects_dsl::status.eq_any([ProjectStatus::Active, ProjectStatus::Archived]),
+            )
+        } else {
+            query.filter(projects_dsl::status.eq(ProjectStatus::Active))
+        }
+    }
+}
@@ -1,10 +1,71 @@
-use crate::schema::projects;
+use crate::{arg::ProjectId, schema::projects, todo::sql::TodoStatus};
 use chrono::NaiveDateTime;
+use clap::ValueEnum;
 use diesel::{
     Insertable, Queryable, Selectable,
+    backend::Backend,
+    deserialize::{FromSql, FromSqlRow, Result},
+    expression::AsExpression,
     prelude::{AsChangeset, Identifiable},
+    serialize::{IsNull, ToSql},
+    sql_types::Integer,
+    sqlite::Sqlite,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::{Display, IntoStaticStr};
+
+/// Represents the lifecycle status of a project.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+    IntoStaticStr,
+    Display,
+    Default,
+    clap::clap_derive::ValueEnum,
+)]
+#[diesel(sql_type = Integer)]
+#[repr(i32)]
+pub enum ProjectStatus {
+    /// The project is being worked on.
+    #[default]
+    Active = 0,
+    /// The project is finished or abandoned and kept for reference.
+    Archived = 1,
+    /// The project is on hold for now.
+    Paused = 2,
+}
+
+impl FromSql<Integer, Sqlite> for ProjectStatus {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> Result<Self> {
+        let value = i32::from_sql(bytes)?;
+        match value {
+            0 => Ok(ProjectStatus::Active),
+            1 => Ok(ProjectStatus::Archived),
+            2 => Ok(ProjectStatus::Paused),
+            _ => Err("Unrecognized variant".into()),
+        }
+    }
+}
+
+impl ToSql<Integer, Sqlite> for ProjectStatus {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, Sqlite>,
+    ) -> diesel::serialize::Result {
+        out.set_value(*self as i32);
+        Ok(IsNull::No)
+    }
+}
 
 /// Represents a new project to be inserted into the database.
 #[derive(Debug, Insertable, Serialize)]
@@ -25,7 +86,7 @@ pub struct NewProject {
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct UpdateProject {
     /// The unique identifier of the project to update.
-    pub id: i32,
+    pub id: ProjectId,
     /// The new name for the project, if it's being changed.
     pub name: Option<String>,
     /// The new description for the project, if it's being changed.
@@ -34,10 +95,42 @@ pub struct UpdateProject {
     pub color: Option<String>,
     /// The timestamp when the project was last updated.
     pub updated_at: NaiveDateTime,
+    /// The new status for the project, if it's being changed.
+    pub status: Option<ProjectStatus>,
+    /// The new archive time, set along with the status: now when archiving, cleared otherwise.
+    pub archived_at: Option<Option<NaiveDateTime>>,
+}
+
+/// A struct used to archive a project, keeping it and its todos out of the default views.
+#[derive(Debug, AsChangeset, Serialize)]
+#[diesel(table_name = projects)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct ArchiveProject {
+    /// The unique identifier of the project to archive.
+    pub id: ProjectId,
+    /// Always `Archived`.
+    pub status: ProjectStatus,
+    /// The timestamp when the project was archived.
+    pub archived_at: NaiveDateTime,
+    /// The timestamp when the project was last updated.
+    pub updated_at: NaiveDateTime,
+}
+
+impl ArchiveProject {
+    /// Archives the project as of now.
+    pub fn new(id: ProjectId) -> Self {
+        let now = chrono::Utc::now().naive_utc();
+        Self {
+            id,
+            status: ProjectStatus::Archived,
+            archived_at: now,
+            updated_at: now,
+        }
+    }
 }
 
 /// Represents a project retrieved from the database.
-#[derive(Debug, Queryable, Selectable, Identifiable, Deserialize)]
+#[derive(Debug, Queryable, Selectable, Identifiable, Serialize, Deserialize)]
 #[diesel(table_name = projects)]
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct Project {
@@ -53,4 +146,118 @@ pub struct Project {
     pub created_at: Option<NaiveDateTime>,
     /// The timestamp when the project was last updated.
     pub updated_at: Option<NaiveDateTime>,
+    /// The lifecycle status of the project.
+    pub status: ProjectStatus,
+    /// The timestamp when the project was archived, if it is.
+    pub archived_at: Option<NaiveDateTime>,
+}
+
+/// The colors `--next_color` cycles through and default project colors are picked from.
+pub const PALETTE: [&str; 12] = [
+    "#0D6EFD", "#6610F2", "#6F42C1", "#D63384", "#DC3545", "#FD7E14", "#FFC107", "#198754",
+    "#20C997", "#0DCAF0", "#2596BE", "#6C757D",
+];
+
+/// A palette color derived from the project id, so a project without its own color
+/// always shows up in the same one.
+pub fn project_default_color(id: i32) -> String {
+    // Knuth's multiplicative hash spreads neighbouring ids over the palette
+    let hash = (id as u32).wrapping_mul(2_654_435_761) >> 16;
+    PALETTE[hash as usize % PALETTE.len()].to_string()
+}
+
+impl Project {
+    /// The color the project name is shown in: its own color or a stable default.
+    pub fn display_color(&self) -> String {
+        self.color
+            .clone()
+            .unwrap_or_else(|| project_default_color(self.id))
+    }
+
+    /// Whether the project is being worked on.
+    pub fn is_active(&self) -> bool {
+        self.status == ProjectStatus::Active
+    }
+
+    /// Whether the project has been archived.
+    pub fn is_archived(&self) -> bool {
+        self.status == ProjectStatus::Archived
+    }
+
+    /// The project as a JSON value, for exports and integrations.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Project serialization should never fail")
+    }
+
+    /// The project as pretty printed JSON.
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Project serialization should never fail")
+    }
+}
+
+/// Formats the non-zero todo counts in status order, e.g. `5 to-do • 2 in-progress • 1 blocked`.
+///
+/// Returns an empty string when every count is zero.
+pub fn todo_summary_line(counts: &HashMap<TodoStatus, usize>) -> String {
+    TodoStatus::value_variants()
+        .iter()
+        .filter_map(|status| {
+            let count = counts.get(status).copied().unwrap_or(0);
+            let name = status.to_possible_value()?;
+            (count > 0).then(|| format!("{} {}", count, name.get_name()))
+        })
+        .collect::<Vec<String>>()
+        .join(" • ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_todo_summary_line() {
+        let counts = HashMap::from([
+            (TodoStatus::Blocked, 1),
+            (TodoStatus::ToDo, 5),
+            (TodoStatus::InProgress, 2),
+            (TodoStatus::Done, 0),
+        ]);
+        assert_eq!(
+            todo_summary_line(&counts),
+            "5 to-do • 2 in-progress • 1 blocked"
+        );
+        assert_eq!(todo_summary_line(&HashMap::new()), "");
+    }
+
+    #[test]
+    fn test_project_default_color_is_stable() {
+        assert_eq!(project_default_color(7), project_default_color(7));
+        assert!(PALETTE.contains(&project_default_color(7).as_str()));
+        assert!(PALETTE.contains(&project_default_color(-3).as_str()));
+    }
+
+    #[test]
+    fn test_project_to_json() {
+        let project = Project {
+            id: 2,
+            name: "Website".to_string(),
+            description: None,
+            color: Some("#0D6EFD".to_string()),
+            created_at: None,
+            updated_at: None,
+            status: ProjectStatus::Paused,
+            archived_at: None,
+        };
+
+        let json = project.to_json();
+        assert_eq!(json["id"], 2);
+        assert_eq!(json["name"], "Website");
+        assert_eq!(json["color"], "#0D6EFD");
+        assert!(project.to_json_string().contains("\"name\": \"Website\""));
+    }
+
+    #[test]
+    fn test_project_default_color_spreads_ids() {
+        assert_ne!(project_default_color(1), project_default_color(2));
+    }
 }
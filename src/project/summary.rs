@@ -0,0 +1,87 @@
+use crate::{project::sql::Project, todo::sql::TodoStatus};
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+/// The most recently updated todo of a project.
+#[derive(Debug)]
+pub struct ProjectActivity {
+    /// The project the activity belongs to.
+    pub project_id: i32,
+    /// The title of the most recently updated todo, if the project has any todos.
+    pub last_todo_title: Option<String>,
+    /// When the most recently updated todo was last updated.
+    pub last_updated_at: Option<NaiveDateTime>,
+}
+
+/// How many of a project's todos are done.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProjectProgress {
+    /// The number of todos with status done.
+    pub done: i64,
+    /// The number of todos in the project.
+    pub total: i64,
+}
+
+impl ProjectProgress {
+    /// Builds the progress from the number of todos in each status.
+    pub fn from_counts(counts: &HashMap<TodoStatus, usize>) -> Self {
+        let total: usize = counts.values().sum();
+        let done = counts.get(&TodoStatus::Done).copied().unwrap_or(0);
+        Self {
+            done: done as i64,
+            total: total as i64,
+        }
+    }
+}
+
+/// A project paired with the aggregate details shown alongside it in listings.
+#[derive(Debug)]
+pub struct ProjectSummary {
+    /// The project being summarized.
+    pub project: Project,
+    /// The number of overdue todos in the project, if it was computed.
+    pub overdue_count: Option<i64>,
+    /// The most recent todo activity in the project, if it was computed.
+    pub last_activity: Option<ProjectActivity>,
+    /// The todo completion of the project, if it was computed.
+    pub progress: Option<ProjectProgress>,
+    /// The number of todos in each status, if it was computed.
+    pub todo_counts: Option<HashMap<TodoStatus, usize>>,
+}
+
+impl ProjectSummary {
+    /// Creates a new `ProjectSummary` with no aggregate details.
+    pub fn new(project: Project) -> Self {
+        Self {
+            project,
+            overdue_count: None,
+            last_activity: None,
+            progress: None,
+            todo_counts: None,
+        }
+    }
+
+    /// Sets the overdue todo count for this summary.
+    pub fn with_overdue_count(mut self, overdue_count: i64) -> Self {
+        self.overdue_count = Some(overdue_count);
+        self
+    }
+
+    /// Sets the most recent todo activity for this summary.
+    pub fn with_last_activity(mut self, last_activity: ProjectActivity) -> Self {
+        self.last_activity = Some(last_activity);
+        self
+    }
+
+    /// Sets the todo completion for this summary.
+    pub fn with_progress(mut self, progress: ProjectProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Sets the number of todos in each status for this summary.
+    pub fn with_todo_counts(mut self, todo_counts: HashMap<TodoStatus, usize>) -> Self {
+        self.todo_counts = Some(todo_counts);
+        self
+    }
+}
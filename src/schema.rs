@@ -8,6 +8,8 @@ diesel::table! {
         color -> Nullable<Text>,
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
+        status -> Integer,
+        archived_at -> Nullable<Timestamp>,
     }
 }
 
@@ -27,6 +29,10 @@ diesel::table! {
         created_at -> Nullable<Timestamp>,
         updated_at -> Nullable<Timestamp>,
         completed_at -> Nullable<Timestamp>,
+        assignee -> Nullable<Text>,
+        blocked_by_id -> Nullable<Integer>,
+        tags -> Nullable<Text>,
+        actual_minutes -> Nullable<Integer>,
     }
 }
 
@@ -0,0 +1,199 @@
+use clap::{ArgMatches, Command};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use std::io;
+
+use crate::{
+    arg::{ProjectId, TuduArg},
+    display::{info_message, print_table, stats_table},
+    error::TuduResult,
+    project::{query::ProjectQuery, sql::Project},
+    schema::{projects::dsl as projects_dsl, todos::dsl as todos_dsl},
+    todo::sql::{Todo, TodoStatus},
+};
+
+/// Aggregate metrics over every todo of a project, as printed by `tudu stats`.
+#[derive(Debug)]
+pub struct ProjectStats {
+    /// The project the metrics are about.
+    pub project: Project,
+    /// Every todo of the project, whatever its status.
+    pub total: usize,
+    /// Todos that are neither started nor finished: to-do, blocked and on-hold.
+    pub open: usize,
+    pub in_progress: usize,
+    pub done: usize,
+    pub cancelled: usize,
+    /// Todos past their due date that are neither done nor cancelled.
+    pub overdue: usize,
+    /// The sum of every estimate, ignoring todos without one.
+    pub total_estimated_minutes: i64,
+    /// The mean over todos that have an estimate, if any do.
+    pub average_estimated_minutes: Option<f64>,
+}
+
+impl ProjectStats {
+    /// Computes the metrics of `project` from all of its todos.
+    pub fn from_todos(project: Project, todos: &[Todo]) -> Self {
+        let count = |status: TodoStatus| todos.iter().filter(|todo| todo.status == status).count();
+        let estimates: Vec<i64> = todos
+            .iter()
+            .filter_map(|todo| todo.estimated_minutes.map(i64::from))
+            .collect();
+        let total_estimated_minutes = estimates.iter().sum();
+        let average_estimated_minutes = (!estimates.is_empty())
+            .then(|| total_estimated_minutes as f64 / estimates.len() as f64);
+
+        Self {
+            project,
+            total: todos.len(),
+            open: count(TodoStatus::ToDo) + count(TodoStatus::Blocked) + count(TodoStatus::OnHold),
+            in_progress: count(TodoStatus::InProgress),
+            done: count(TodoStatus::Done),
+            cancelled: count(TodoStatus::Cancelled),
+            overdue: todos.iter().filter(|todo| todo.is_overdue()).count(),
+            total_estimated_minutes,
+            average_estimated_minutes,
+        }
+    }
+}
+
+pub fn stats_command() -> Command {
+    Command::new("stats")
+        .about("Print aggregate metrics for every active project, or a single project")
+        .arg(TuduArg::ProjectId.into_arg(true))
+}
+
+/// Computes the metrics of every active project, or of `project_id` whatever its status.
+///
+/// Fails with `NotFound` when `project_id` does not exist.
+pub fn compute_stats(
+    conn: &mut SqliteConnection,
+    project_id: Option<ProjectId>,
+) -> TuduResult<Vec<ProjectStats>> {
+    let stats = conn.transaction(|conn| -> Result<_, diesel::result::Error> {
+        let projects = match project_id {
+            Some(project_id) => vec![
+                ProjectQuery::new()
+                    .include_inactive()
+                    .build()
+                    .filter(projects_dsl::id.eq(project_id))
+                    .first::<Project>(conn)?,
+            ],
+            None => ProjectQuery::new()
+                .build()
+                .order(projects_dsl::id.asc())
+                .load::<Project>(conn)?,
+        };
+
+        let project_ids: Vec<i32> = projects.iter().map(|project| project.id).collect();
+        let todos = todos_dsl::todos
+            .filter(todos_dsl::project_id.eq_any(project_ids))
+            .load::<Todo>(conn)?;
+
+        Ok(projects
+            .into_iter()
+            .map(|project| {
+                let project_todos: Vec<Todo> = todos
+                    .iter()
+                    .filter(|todo| todo.project_id == project.id)
+                    .cloned()
+                    .collect();
+                ProjectStats::from_todos(project, &project_todos)
+            })
+            .collect())
+    })?;
+    Ok(stats)
+}
+
+/// Prints the metrics of every active project, or the one passed with `--project_id`.
+pub fn handle_stats_command(matches: &ArgMatches, conn: &mut SqliteConnection) -> TuduResult<()> {
+    let project_id = matches
+        .get_one::<i32>(TuduArg::ProjectId.name())
+        .copied()
+        .map(ProjectId);
+
+    let stats = compute_stats(conn, project_id)?;
+    if stats.is_empty() {
+        info_message("No active projects found.");
+        return Ok(());
+    }
+
+    let (headers, rows) = stats_table(&stats);
+    print_table(&mut io::stdout(), headers, rows)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        infrastructure::database::{
+            in_memory_connection, insert_test_project, insert_test_todo, test_new_todo,
+        },
+        todo::sql::NewTodo,
+    };
+    use chrono::{Duration, Utc};
+
+    fn insert_todo(
+        conn: &mut SqliteConnection,
+        project_id: i32,
+        status: TodoStatus,
+        estimated_minutes: Option<i32>,
+        overdue: bool,
+    ) {
+        let due_date = overdue.then(|| Utc::now().naive_utc() - Duration::days(1));
+        insert_test_todo(
+            conn,
+            NewTodo {
+                status,
+                due_date,
+                estimated_minutes,
+                ..test_new_todo(project_id, "Task")
+            },
+        );
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Stats");
+        let other = insert_test_project(&mut conn, "Other");
+        insert_todo(&mut conn, project.id, TodoStatus::ToDo, Some(30), true);
+        insert_todo(&mut conn, project.id, TodoStatus::Blocked, None, false);
+        insert_todo(
+            &mut conn,
+            project.id,
+            TodoStatus::InProgress,
+            Some(60),
+            false,
+        );
+        insert_todo(&mut conn, project.id, TodoStatus::Done, None, true);
+        insert_todo(
+            &mut conn,
+            project.id,
+            TodoStatus::Cancelled,
+            Some(15),
+            false,
+        );
+        insert_todo(&mut conn, other.id, TodoStatus::ToDo, None, false);
+
+        let stats = compute_stats(&mut conn, Some(ProjectId(project.id))).unwrap();
+        assert_eq!(stats.len(), 1);
+        let stats = &stats[0];
+        assert_eq!(stats.total, 5);
+        assert_eq!(stats.open, 2);
+        assert_eq!(stats.in_progress, 1);
+        assert_eq!(stats.done, 1);
+        assert_eq!(stats.cancelled, 1);
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(stats.total_estimated_minutes, 105);
+        assert_eq!(stats.average_estimated_minutes, Some(35.0));
+
+        let all = compute_stats(&mut conn, None).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].total, 1);
+        assert_eq!(all[1].average_estimated_minutes, None);
+
+        assert!(compute_stats(&mut conn, Some(ProjectId(999))).is_err());
+    }
+}
@@ -1,70 +1,208 @@
 use crate::{
-    arg::{TuduArg, ValidDateTime, ValidUrl, parse_required_project_id},
-    display::{Display, Prefix},
+    arg::{
+        OutputFormat, ProjectId, TodoId, TuduArg, ValidDateTime, ValidTags, ValidUrl,
+        get_negatable_flag, is_arg_explicit, looks_like_markdown, parse_flag_or_config_project_id,
+        parse_required_project_id, strip_markdown,
+    },
+    config::{parse_tudu_file, write_project_id_to_config},
+    display::{
+        Display, Message, Prefix, config_set_message, confirm_prompt, display_datetime,
+        footer_message, format_day_heading, group_heading, info_message, output_writer,
+        output_written_message, print_table, project_id_source_message, section_heading,
+        todo_breadcrumb, todo_calendar, todo_table, warning_message, workload_message,
+    },
     error::{TuduError, TuduResult},
-    infrastructure::database,
-    project::sql::Project,
-    schema::todos::dsl as todos_dsl,
-    todo::sql::{CloseTodo, NewTodo, Todo, TodoPriority, TodoStatus, UpdateTodo},
+    infrastructure::env,
+    project::{query::ProjectQuery, sql::Project},
+    schema::todos::{self, dsl as todos_dsl},
+    todo::sql::{
+        CloseTodo, NewTodo, SearchTodo, Todo, TodoPriority, TodoStatus, UpdateTodo, escape_like,
+        search,
+    },
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use clap::{
+    ArgAction, ArgMatches, Command, ValueEnum,
+    builder::{PossibleValuesParser, TypedValueParser},
 };
-use clap::{ArgMatches, Command};
 use diesel::{
-    BoolExpressionMethods, Connection, ExpressionMethods, QueryDsl, RunQueryDsl, insert_into,
+    Connection, EscapeExpressionMethods, ExpressionMethods, IntoSql, OptionalExtension, QueryDsl,
+    RunQueryDsl, SqliteConnection, TextExpressionMethods, delete, insert_into,
+    sql_types::{Nullable, Text},
+    sqlite::Sqlite,
     update,
 };
+use std::{io::Write, path::PathBuf};
 
 pub fn new_todo_command() -> Command {
     Command::new("todo").args([
-        TuduArg::Title.into_arg(false).required(true),
+        TuduArg::Title
+            .into_arg(false)
+            .required_unless_present(TuduArg::CopyFrom.name()),
         TuduArg::ProjectId.into_arg(false),
         TuduArg::ParentId.into_arg(true),
         TuduArg::Description.into_arg(true),
         TuduArg::Priority.into_arg(true),
+        TuduArg::Status.into_arg(true),
         TuduArg::DueDate.into_arg(true),
         TuduArg::EstimatedMinutes.into_arg(true),
+        TuduArg::ActualMinutes.into_arg(true),
         TuduArg::Location.into_arg(true),
         TuduArg::Url.into_arg(true),
+        TuduArg::Assignee.into_arg(true),
+        TuduArg::Tags.into_arg(true),
+        TuduArg::CopyFrom.into_arg(true),
+        TuduArg::StripMarkdown
+            .into_arg(true)
+            .conflicts_with(TuduArg::NoStripMarkdown.name()),
+        TuduArg::NoStripMarkdown.into_arg(true),
+        TuduArg::AllowPastDue.into_arg(true),
     ])
 }
 
-fn parse_new_todo_command_matches(matches: &ArgMatches) -> TuduResult<NewTodo> {
-    let project_id = parse_required_project_id(matches)?;
+// Strips markdown from the title when asked to, or when it looks like markdown and that wasn't
+// opted out of
+fn clean_title(title: &str, matches: &ArgMatches) -> String {
+    let strip = matches.get_flag(TuduArg::StripMarkdown.name())
+        || (!matches.get_flag(TuduArg::NoStripMarkdown.name()) && looks_like_markdown(title));
+    let stripped = if strip {
+        strip_markdown(title)
+    } else {
+        title.to_owned()
+    };
+    if stripped.is_empty() {
+        title.to_owned()
+    } else {
+        stripped
+    }
+}
+
+fn parse_new_todo_command_matches(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<NewTodo> {
+    let (project_id, source) = parse_required_project_id(matches, conn)?;
+    project_id_source_message(project_id, &source);
     let title: &String = matches
         .get_one(TuduArg::Title.name())
-        .ok_or_else(|| TuduError::RequiredArgumentError)?;
+        .ok_or(TuduError::RequiredArgumentError)?;
     let parent_id: Option<&i32> = matches.get_one(TuduArg::ParentId.name());
     let description: Option<&String> = matches.get_one(TuduArg::Description.name());
     let priority: Option<&TodoPriority> = matches.get_one(TuduArg::Priority.name());
+    let status: Option<&TodoStatus> = matches.get_one(TuduArg::Status.name());
     let due_date: Option<&ValidDateTime> = matches.get_one(TuduArg::DueDate.name());
     let estimated_minutes: Option<&i32> = matches.get_one(TuduArg::EstimatedMinutes.name());
     let location: Option<&String> = matches.get_one(TuduArg::Location.name());
     let url: Option<&ValidUrl> = matches.get_one(TuduArg::Url.name());
+    let assignee: Option<&String> = matches.get_one(TuduArg::Assignee.name());
+    let tags: Option<&ValidTags> = matches.get_one(TuduArg::Tags.name());
+    let actual_minutes: Option<&i32> = matches.get_one(TuduArg::ActualMinutes.name());
 
     Ok(NewTodo {
         project_id,
-        title: title.to_owned(),
-        parent_id: parent_id.copied(),
+        title: clean_title(title, matches),
+        parent_id: parent_id.copied().map(TodoId),
         description: description.map(|s| s.to_owned()),
         priority: priority.copied().unwrap_or_default(),
         due_date: due_date.map(|d| d.0),
         estimated_minutes: estimated_minutes.map(|m| m.to_owned()),
         location: location.map(|s| s.to_owned()),
-        url: url.map(|u| u.0.to_string()),
-        status: TodoStatus::default(),
+        url: url.map(|u| u.to_string()),
+        status: status.copied().unwrap_or_default(),
         created_at: None,
         updated_at: None,
         completed_at: None,
-    })
+        assignee: assignee.cloned(),
+        tags: tags.map(|t| t.to_string()),
+        actual_minutes: actual_minutes.copied(),
+    }
+    .with_default_timestamps())
+}
+
+// Overrides the copied todo with every arg that was explicitly passed
+fn apply_new_todo_command_overrides(mut new_todo: NewTodo, matches: &ArgMatches) -> NewTodo {
+    if let Some(project_id) = matches.get_one::<i32>(TuduArg::ProjectId.name()) {
+        new_todo.project_id = ProjectId(*project_id);
+    }
+    if let Some(title) = matches.get_one::<String>(TuduArg::Title.name()) {
+        new_todo.title = clean_title(title, matches);
+    }
+    if let Some(parent_id) = matches.get_one::<i32>(TuduArg::ParentId.name()) {
+        new_todo.parent_id = Some(TodoId(*parent_id));
+    }
+    if let Some(description) = matches.get_one::<String>(TuduArg::Description.name()) {
+        new_todo.description = Some(description.to_owned());
+    }
+    if is_arg_explicit(matches, TuduArg::Priority)
+        && let Some(priority) = matches.get_one::<TodoPriority>(TuduArg::Priority.name())
+    {
+        new_todo.priority = *priority;
+    }
+    if is_arg_explicit(matches, TuduArg::Status)
+        && let Some(status) = matches.get_one::<TodoStatus>(TuduArg::Status.name())
+    {
+        new_todo.status = *status;
+    }
+    if let Some(due_date) = matches.get_one::<ValidDateTime>(TuduArg::DueDate.name()) {
+        new_todo.due_date = Some(due_date.0);
+    }
+    if let Some(minutes) = matches.get_one::<i32>(TuduArg::EstimatedMinutes.name()) {
+        new_todo.estimated_minutes = Some(*minutes);
+    }
+    if let Some(minutes) = matches.get_one::<i32>(TuduArg::ActualMinutes.name()) {
+        new_todo.actual_minutes = Some(*minutes);
+    }
+    if let Some(location) = matches.get_one::<String>(TuduArg::Location.name()) {
+        new_todo.location = Some(location.to_owned());
+    }
+    if let Some(url) = matches.get_one::<ValidUrl>(TuduArg::Url.name()) {
+        new_todo.url = Some(url.to_string());
+    }
+    if let Some(assignee) = matches.get_one::<String>(TuduArg::Assignee.name()) {
+        new_todo.assignee = Some(assignee.to_owned());
+    }
+    if let Some(tags) = matches.get_one::<ValidTags>(TuduArg::Tags.name()) {
+        new_todo.tags = Some(tags.to_string());
+    }
+    new_todo
 }
 
-pub fn handle_new_todo_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
-    let new_todo = parse_new_todo_command_matches(matches)?;
+pub fn handle_new_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let copy_from: Option<i32> = matches.get_one(TuduArg::CopyFrom.name()).copied();
+    let allow_past_due = matches.get_flag(TuduArg::AllowPastDue.name());
 
-    let res = connection.transaction(move |conn| {
-        if new_todo.parent_id.is_some() {
+    let res = conn.transaction(|conn| {
+        let new_todo = match copy_from {
+            Some(source_id) => {
+                let source = todos_dsl::todos
+                    .filter(todos_dsl::id.eq(source_id))
+                    .first::<Todo>(conn)?;
+                apply_new_todo_command_overrides(NewTodo::from(source), matches)
+                    .with_default_timestamps()
+            }
+            None => parse_new_todo_command_matches(matches, conn)?,
+        };
+        if new_todo.status.is_terminal() {
+            return Err(TuduError::ValidationError(format!(
+                "A new todo can't start as {}. Create it first, then close it with tudu close todo.",
+                new_todo.status
+            )));
+        }
+        if !allow_past_due
+            && let Some(due_date) = new_todo.due_date
+            && due_date < chrono::Utc::now().naive_utc()
+        {
+            warning_message(
+                &format!("Due date is in the past: {}", display_datetime(due_date)),
+                "Did you mean a different date?",
+            );
+        }
+        if let Some(parent_id) = new_todo.parent_id {
             let parents_parent_id = todos_dsl::todos
-                .filter(todos_dsl::id.eq(new_todo.parent_id.unwrap()))
+                .filter(todos_dsl::id.eq(parent_id))
                 .select(todos_dsl::parent_id)
                 .first::<Option<i32>>(conn)?;
             if parents_parent_id.is_some() {
@@ -76,10 +214,19 @@ pub fn handle_new_todo_command(matches: &ArgMatches) -> TuduResult<()> {
         insert_into(todos_dsl::todos)
             .values(new_todo)
             .get_result::<Todo>(conn)
-            .map_err(|e| TuduError::from(e))
+            .map_err(TuduError::from)
     })?;
 
     res.to_message(Some(Prefix::New)).display();
+    if res.status == TodoStatus::Blocked && res.description.is_none() {
+        warning_message(
+            "Blocked todos should have a description explaining what's blocking them",
+            "Add --description 'Blocked by: ...'",
+        );
+    }
+    if let Some(source_id) = copy_from {
+        info_message(&format!("Copied fields from #{}", source_id));
+    }
     Ok(())
 }
 
@@ -90,41 +237,76 @@ pub fn update_todo_command() -> Command {
         TuduArg::ParentId.into_arg(true),
         TuduArg::Title.into_arg(true),
         TuduArg::Description.into_arg(true),
+        TuduArg::ClearDescription
+            .into_arg(true)
+            .conflicts_with(TuduArg::Description.name()),
         TuduArg::Status.into_arg(true),
         TuduArg::Priority.into_arg(true),
         TuduArg::DueDate.into_arg(true),
         TuduArg::EstimatedMinutes.into_arg(true),
+        TuduArg::ActualMinutes.into_arg(true),
         TuduArg::Location.into_arg(true),
         TuduArg::Url.into_arg(true),
+        TuduArg::Assignee.into_arg(true),
+        TuduArg::Tags.into_arg(true),
+        TuduArg::BlockedBy.into_arg(true),
+        TuduArg::SetDefault.into_arg(true),
+        TuduArg::ForceStatus.into_arg(true),
+        TuduArg::Reopen.into_arg(true),
+        TuduArg::Force.into_arg(true),
     ])
 }
 
 fn parse_update_todo_command_matches(matches: &ArgMatches) -> TuduResult<UpdateTodo> {
     let id: &i32 = matches
         .get_one(TuduArg::TodoId.name())
-        .ok_or_else(|| TuduError::RequiredArgumentError)?;
+        .ok_or(TuduError::RequiredArgumentError)?;
     let project_id: Option<&i32> = matches.get_one(TuduArg::ProjectId.name());
     let parent_id: Option<&i32> = matches.get_one(TuduArg::ParentId.name());
     let title: Option<&String> = matches.get_one(TuduArg::Title.name());
     let description: Option<&String> = matches.get_one(TuduArg::Description.name());
-    let status: Option<&TodoStatus> = matches.get_one(TuduArg::Status.name());
+    let clear_description = matches.get_flag(TuduArg::ClearDescription.name());
     let priority: Option<&TodoPriority> = matches.get_one(TuduArg::Priority.name());
     let due_date: Option<&ValidDateTime> = matches.get_one(TuduArg::DueDate.name());
     let estimated_minutes: Option<&i32> = matches.get_one(TuduArg::EstimatedMinutes.name());
     let location: Option<&String> = matches.get_one(TuduArg::Location.name());
     let url: Option<&ValidUrl> = matches.get_one(TuduArg::Url.name());
+    let assignee: Option<&String> = matches.get_one(TuduArg::Assignee.name());
+    let blocked_by: Option<&i32> = matches.get_one(TuduArg::BlockedBy.name());
+    let tags: Option<&ValidTags> = matches.get_one(TuduArg::Tags.name());
+    let actual_minutes: Option<&i32> = matches.get_one(TuduArg::ActualMinutes.name());
+
+    // Being blocked by another todo implies the blocked status unless one was passed.
+    // The status arg defaults to to-do, so only use it when it was actually given.
+    let status: Option<&TodoStatus> = if is_arg_explicit(matches, TuduArg::Status) {
+        matches.get_one(TuduArg::Status.name())
+    } else if blocked_by.is_some() {
+        Some(&TodoStatus::Blocked)
+    } else {
+        None
+    };
+    if blocked_by.is_some() && status != Some(&TodoStatus::Blocked) {
+        return Err(TuduError::UnSupportedError(
+            "--blocked_by can only be used with --status blocked.".to_string(),
+        ));
+    }
 
     Ok(UpdateTodo {
-        id: id.clone(),
-        project_id: project_id.copied(),
+        id: TodoId(*id),
+        project_id: project_id.copied().map(ProjectId),
         title: title.map(|title| title.to_owned()),
-        parent_id: parent_id.copied(),
-        description: description.map(|s| s.to_owned()),
+        parent_id: parent_id.copied().map(TodoId),
+        // A nested None writes NULL, while a plain None leaves the column untouched
+        description: if clear_description {
+            Some(None)
+        } else {
+            description.map(|s| Some(s.to_owned()))
+        },
         priority: priority.copied(),
         due_date: due_date.map(|d| d.0),
         estimated_minutes: estimated_minutes.copied(),
         location: location.map(|s| s.to_owned()),
-        url: url.map(|u| u.0.to_string()),
+        url: url.map(|u| u.to_string()),
         status: status.copied(),
         updated_at: chrono::Utc::now().naive_utc(),
         completed_at: match status {
@@ -135,148 +317,1913 @@ fn parse_update_todo_command_matches(matches: &ArgMatches) -> TuduResult<UpdateT
             },
             None => None,
         },
+        assignee: assignee.cloned(),
+        blocked_by_id: blocked_by.copied().map(TodoId),
+        tags: tags.map(|t| t.to_string()),
+        actual_minutes: actual_minutes.copied(),
     })
 }
 
-pub fn handle_update_todo_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
+pub fn handle_update_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
     let update_todo = parse_update_todo_command_matches(matches)?;
+    let set_default = matches.get_flag(TuduArg::SetDefault.name());
+    let force_status = matches.get_flag(TuduArg::ForceStatus.name());
+    let reopen = matches.get_flag(TuduArg::Reopen.name());
+    let force = matches.get_flag(TuduArg::Force.name());
 
-    let res = connection.transaction(move |conn| {
+    let res = conn.transaction(move |conn| -> TuduResult<Todo> {
+        let mut current_status = todos_dsl::todos
+            .filter(todos_dsl::id.eq(update_todo.id))
+            .select(todos_dsl::status)
+            .first::<TodoStatus>(conn)?;
+        if current_status.is_terminal() {
+            if reopen {
+                update(todos_dsl::todos.filter(todos_dsl::id.eq(update_todo.id)))
+                    .set((
+                        todos_dsl::status.eq(TodoStatus::ToDo),
+                        todos_dsl::completed_at.eq(None::<NaiveDateTime>),
+                    ))
+                    .execute(conn)?;
+                current_status = TodoStatus::ToDo;
+            } else if !force {
+                return Err(TuduError::ValidationError(format!(
+                    "Todo #{} is already closed. Use --reopen to re-open it first, or --force to update anyway",
+                    update_todo.id
+                )));
+            }
+        }
+
+        if let Some(new_status) = update_todo.status
+            && !force_status
+            && !current_status.can_transition_to(new_status)
+        {
+            return Err(TuduError::ValidationError(format!(
+                "Cannot transition from {} to {}. Pass --force_status to set it anyway.",
+                current_status, new_status
+            )));
+        }
         update(todos_dsl::todos.filter(todos_dsl::id.eq(update_todo.id)))
             .set(update_todo)
             .get_result::<Todo>(conn)
+            .map_err(TuduError::from)
     })?;
 
     res.to_message(Some(Prefix::Update)).display();
+    if res.status == TodoStatus::Blocked && res.description.is_none() && res.blocked_by_id.is_none()
+    {
+        warning_message(
+            "Blocked todos should have a description explaining what's blocking them",
+            "Add --description 'Blocked by: ...'",
+        );
+    }
+
+    if set_default {
+        set_default_project(res.project_id)?;
+    }
     Ok(())
 }
 
-pub fn close_todo_command() -> Command {
-    Command::new("todo").args([TuduArg::TodoId.into_arg(false).required(true)])
+// Writes the project id to the .tudu in the current directory unless it is already there
+fn set_default_project(project_id: i32) -> TuduResult<()> {
+    let config_path = std::env::current_dir()?.join(".tudu");
+    let current = parse_tudu_file(config_path.clone())
+        .ok()
+        .and_then(|mut values| values.remove("PROJECT_ID"));
+    if current.as_deref() == Some(project_id.to_string().as_str()) {
+        info_message(&format!(
+            "Project #{} is already set as default",
+            project_id
+        ));
+        return Ok(());
+    }
+
+    write_project_id_to_config(project_id)?;
+    config_set_message("PROJECT_ID", &project_id.to_string(), &config_path);
+    Ok(())
 }
 
-fn parse_close_todo_command_matches(matches: &ArgMatches) -> TuduResult<CloseTodo> {
+pub fn rename_todo_command() -> Command {
+    Command::new("todo").args([
+        TuduArg::TodoId.into_arg(false).required(true),
+        TuduArg::Title.into_arg(true).required(true),
+    ])
+}
+
+fn parse_rename_todo_command_matches(matches: &ArgMatches) -> TuduResult<UpdateTodo> {
     let id: &i32 = matches
         .get_one(TuduArg::TodoId.name())
-        .ok_or_else(|| TuduError::RequiredArgumentError)?;
+        .ok_or(TuduError::RequiredArgumentError)?;
+    let title: &String = matches
+        .get_one(TuduArg::Title.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
 
-    Ok(CloseTodo {
-        id: id.clone(),
+    Ok(UpdateTodo {
+        id: TodoId(*id),
+        project_id: None,
+        parent_id: None,
+        title: Some(title.clone()),
+        description: None,
+        status: None,
+        priority: None,
+        due_date: None,
+        estimated_minutes: None,
+        location: None,
+        url: None,
         updated_at: chrono::Utc::now().naive_utc(),
-        status: TodoStatus::Done,
-        completed_at: chrono::Utc::now().naive_utc(),
+        completed_at: None,
+        assignee: None,
+        blocked_by_id: None,
+        tags: None,
+        actual_minutes: None,
     })
 }
 
-pub fn handle_close_todo_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
-    let close_todo = parse_close_todo_command_matches(matches)?;
+pub fn handle_rename_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let rename_todo = parse_rename_todo_command_matches(matches)?;
 
-    let res = connection.transaction(move |conn| {
-        update(todos_dsl::todos.filter(todos_dsl::id.eq(close_todo.id)))
-            .set(close_todo)
+    let res = conn.transaction(move |conn| {
+        update(todos_dsl::todos.filter(todos_dsl::id.eq(rename_todo.id)))
+            .set(rename_todo)
             .get_result::<Todo>(conn)
     })?;
 
-    res.to_message(Some(Prefix::Close)).display();
+    res.to_message(Some(Prefix::Update)).display();
+    Ok(())
+}
+
+pub fn close_todo_command() -> Command {
+    Command::new("todo")
+        .args([
+            TuduArg::TodoId.into_arg(false).required(true),
+            TuduArg::Status.into_arg(true),
+            TuduArg::Reason.into_arg(true),
+            TuduArg::WithNote.into_arg(true),
+            TuduArg::ActualMinutes.into_arg(true),
+        ])
+        .args(TuduArg::Cascade.into_negatable_args(true))
+}
+
+// The todo to close, the closing reason and note if any were given, and whether to cascade
+type CloseTodoArgs = (CloseTodo, Option<String>, Option<String>, bool);
+
+fn parse_close_todo_command_matches(matches: &ArgMatches) -> TuduResult<CloseTodoArgs> {
+    let id: &i32 = matches
+        .get_one(TuduArg::TodoId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    // Status has a default for the other commands, so only honor it when it was passed
+    let status = if is_arg_explicit(matches, TuduArg::Status) {
+        matches
+            .get_one::<TodoStatus>(TuduArg::Status.name())
+            .copied()
+            .unwrap_or(TodoStatus::Done)
+    } else {
+        TodoStatus::Done
+    };
+    // Closing with an open status would set completed_at on a todo that isn't closed
+    if !status.is_terminal() {
+        return Err(TuduError::ValidationError(format!(
+            "A todo can only be closed as done or cancelled, not {}",
+            status
+        )));
+    }
+    let reason: Option<&String> = matches.get_one(TuduArg::Reason.name());
+    // A blank note is treated like no note at all
+    let note = matches
+        .get_one::<String>(TuduArg::WithNote.name())
+        .map(|note| note.trim().to_string())
+        .filter(|note| !note.is_empty());
+    let cascade = get_negatable_flag(matches, TuduArg::Cascade, true);
+
+    Ok((
+        CloseTodo {
+            id: TodoId(*id),
+            updated_at: chrono::Utc::now().naive_utc(),
+            status,
+            completed_at: chrono::Utc::now().naive_utc(),
+            description: None,
+            actual_minutes: matches.get_one(TuduArg::ActualMinutes.name()).copied(),
+        },
+        reason.cloned(),
+        note,
+        cascade,
+    ))
+}
+
+// Appends the closing reason to an existing description
+fn description_with_reason(existing: Option<String>, status: TodoStatus, reason: &str) -> String {
+    let label = if status == TodoStatus::Cancelled {
+        "Cancelled"
+    } else {
+        "Closed"
+    };
+    match existing {
+        Some(desc) => format!("{}\n\n{}: {}", desc, label, reason),
+        None => format!("{}: {}", label, reason),
+    }
+}
+
+// Appends the completion note, stamped with the completion time, to an existing description
+fn description_with_note(
+    existing: Option<String>,
+    note: &str,
+    completed_at: NaiveDateTime,
+) -> String {
+    let line = format!(
+        "Completed: {} ({})",
+        note,
+        completed_at.format("%Y-%m-%d %H:%M")
+    );
+    match existing {
+        Some(desc) => format!("{}\n\n{}", desc, line),
+        None => line,
+    }
+}
+
+pub fn handle_close_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let (mut close_todo, reason, note, cascade) = parse_close_todo_command_matches(matches)?;
+    let has_description_change = reason.is_some() || note.is_some();
+
+    let (res, closed_subtodos) = conn.transaction(
+        move |conn| -> Result<(Todo, Vec<Todo>), diesel::result::Error> {
+            if has_description_change {
+                let mut description = todos_dsl::todos
+                    .filter(todos_dsl::id.eq(close_todo.id))
+                    .select(todos_dsl::description)
+                    .first::<Option<String>>(conn)?;
+                if let Some(reason) = reason {
+                    description = Some(description_with_reason(
+                        description,
+                        close_todo.status,
+                        &reason,
+                    ));
+                }
+                if let Some(note) = note {
+                    description = Some(description_with_note(
+                        description,
+                        &note,
+                        close_todo.completed_at,
+                    ));
+                }
+                close_todo.description = description;
+            }
+            let parent_id = close_todo.id;
+            let status = close_todo.status;
+            let now = close_todo.updated_at;
+            let todo = update(todos_dsl::todos.filter(todos_dsl::id.eq(parent_id)))
+                .set(close_todo)
+                .get_result::<Todo>(conn)?;
+
+            // Direct subtodos that are still open are closed along with their parent
+            let closed_subtodos = if cascade {
+                update(
+                    todos_dsl::todos
+                        .filter(todos_dsl::parent_id.eq(parent_id))
+                        .filter(
+                            todos_dsl::status.ne_all([TodoStatus::Done, TodoStatus::Cancelled]),
+                        ),
+                )
+                .set((
+                    todos_dsl::status.eq(status),
+                    todos_dsl::completed_at.eq(now),
+                    todos_dsl::updated_at.eq(now),
+                ))
+                .get_results::<Todo>(conn)?
+            } else {
+                Vec::new()
+            };
+            Ok((todo, closed_subtodos))
+        },
+    )?;
+
+    if has_description_change {
+        res.to_detailed_message(Some(Prefix::Close)).display();
+    } else {
+        res.to_message(Some(Prefix::Close)).display();
+    }
+    for subtodo in &closed_subtodos {
+        subtodo.to_message(None).display();
+    }
+    if !closed_subtodos.is_empty() {
+        info_message(&format!(
+            "Closed todo #{} and {} subtodos",
+            res.id,
+            closed_subtodos.len()
+        ));
+    }
+    Ok(())
+}
+
+pub fn reopen_todo_command() -> Command {
+    Command::new("todo").arg(TuduArg::TodoId.into_arg(false).required(true))
+}
+
+/// Sets a done or cancelled todo back to to-do and clears its completion time.
+pub fn handle_reopen_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let id: i32 = *matches
+        .get_one(TuduArg::TodoId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+
+    let res = conn.transaction(move |conn| -> TuduResult<Todo> {
+        let status = todos_dsl::todos
+            .filter(todos_dsl::id.eq(id))
+            .select(todos_dsl::status)
+            .first::<TodoStatus>(conn)?;
+        if !status.is_terminal() {
+            return Err(TuduError::ValidationError(format!(
+                "Todo #{} is {}, only done or cancelled todos can be reopened",
+                id, status
+            )));
+        }
+        update(todos_dsl::todos.filter(todos_dsl::id.eq(id)))
+            .set((
+                todos_dsl::status.eq(TodoStatus::ToDo),
+                todos_dsl::completed_at.eq(None::<NaiveDateTime>),
+                todos_dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result::<Todo>(conn)
+            .map_err(TuduError::from)
+    })?;
+
+    res.to_message(Some(Prefix::Reopen)).display();
+    Ok(())
+}
+
+pub fn duplicate_todo_command() -> Command {
+    Command::new("todo").args([
+        TuduArg::TodoId.into_arg(false).required(true),
+        TuduArg::Title.into_arg(true),
+    ])
+}
+
+/// Inserts a copy of a todo as a new, open todo, optionally with a different title.
+pub fn handle_duplicate_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let id: i32 = *matches
+        .get_one(TuduArg::TodoId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    let title: Option<String> = matches.get_one(TuduArg::Title.name()).cloned();
+
+    let res = conn.transaction(move |conn| {
+        let source = todos_dsl::todos
+            .filter(todos_dsl::id.eq(id))
+            .first::<Todo>(conn)?;
+        let mut new_todo = NewTodo::from(source).with_default_timestamps();
+        if let Some(title) = title {
+            new_todo.title = title;
+        }
+        insert_into(todos_dsl::todos)
+            .values(new_todo)
+            .get_result::<Todo>(conn)
+    })?;
+
+    res.to_message(Some(Prefix::New)).display();
+    Ok(())
+}
+
+pub fn move_todo_command() -> Command {
+    Command::new("todo").args([
+        TuduArg::TodoId.into_arg(false).required(true),
+        TuduArg::ProjectId.into_arg(true).required(true),
+    ])
+}
+
+/// Moves a top-level todo and all of its subtodos to another active project.
+///
+/// Returns the moved todo and the number of subtodos that followed it.
+fn move_todo(
+    conn: &mut SqliteConnection,
+    id: TodoId,
+    project_id: ProjectId,
+) -> TuduResult<(Todo, usize)> {
+    use crate::schema::projects::dsl as projects_dsl;
+
+    conn.transaction(|conn| {
+        let todo = todos_dsl::todos
+            .filter(todos_dsl::id.eq(id))
+            .first::<Todo>(conn)?;
+        if let Some(parent_id) = todo.parent_id {
+            return Err(TuduError::ValidationError(format!(
+                "Todo #{} is a subtodo of #{}. Move #{} instead to keep them together.",
+                id, parent_id, parent_id
+            )));
+        }
+        if todo.project_id == project_id.0 {
+            return Err(TuduError::ValidationError(format!(
+                "Todo #{} is already in project #{}",
+                id, project_id
+            )));
+        }
+        ProjectQuery::new()
+            .build()
+            .filter(projects_dsl::id.eq(project_id))
+            .select(projects_dsl::id)
+            .first::<i32>(conn)?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let moved = update(todos_dsl::todos.filter(todos_dsl::id.eq(id)))
+            .set((
+                todos_dsl::project_id.eq(project_id),
+                todos_dsl::updated_at.eq(now),
+            ))
+            .get_result::<Todo>(conn)?;
+        let subtodos = update(todos_dsl::todos.filter(todos_dsl::parent_id.eq(id)))
+            .set((
+                todos_dsl::project_id.eq(project_id),
+                todos_dsl::updated_at.eq(now),
+            ))
+            .execute(conn)?;
+        Ok((moved, subtodos))
+    })
+}
+
+/// Moves a todo to the project passed with `--project_id`, taking its subtodos along.
+pub fn handle_move_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let id: i32 = *matches
+        .get_one(TuduArg::TodoId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    let project_id: i32 = *matches
+        .get_one(TuduArg::ProjectId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+
+    let (todo, subtodos) = move_todo(conn, TodoId(id), ProjectId(project_id))?;
+
+    todo.to_message(Some(Prefix::Move)).display();
+    info_message(&format!(
+        "Moved 1 todo and {} {} to project #{}",
+        subtodos,
+        if subtodos == 1 { "subtodo" } else { "subtodos" },
+        project_id
+    ));
     Ok(())
 }
 
 pub fn view_todo_command() -> Command {
-    Command::new("todo").args([TuduArg::TodoId.into_arg(false).required(true)])
+    Command::new("todo").args([
+        TuduArg::TodoId.into_arg(false).required(true),
+        TuduArg::Output.into_arg(true),
+        // A single todo has no table layout, so `wide` isn't accepted
+        TuduArg::Format.into_arg(true).value_parser(
+            PossibleValuesParser::new(["normal", "minimal"])
+                .try_map(|format: String| OutputFormat::from_str(&format, false)),
+        ),
+        TuduArg::DebugDisplay
+            .into_arg(true)
+            .conflicts_with(TuduArg::Format.name()),
+    ])
 }
 
 fn parse_view_todo_command_matches(matches: &ArgMatches) -> TuduResult<i32> {
     let id: &i32 = matches
         .get_one(TuduArg::TodoId.name())
-        .ok_or_else(|| TuduError::RequiredArgumentError)?;
+        .ok_or(TuduError::RequiredArgumentError)?;
 
-    Ok(id.clone())
+    Ok(*id)
 }
 
-pub fn handle_view_todo_command(matches: &ArgMatches) -> TuduResult<()> {
+// The todo, its direct children, its project, the todo blocking it and its parent
+type ViewedTodo = (Todo, Vec<Todo>, Project, Option<Todo>, Option<Todo>);
+
+pub fn handle_view_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
     use crate::schema::projects::dsl as projects_dsl;
 
-    let mut connection = database::database_connection();
     let view_todo_id = parse_view_todo_command_matches(matches)?;
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let format = matches
+        .get_one::<OutputFormat>(TuduArg::Format.name())
+        .copied()
+        .unwrap_or_default();
+    let debug_display = matches.get_flag(TuduArg::DebugDisplay.name());
 
-    let (todo, todo_children, project) = connection.transaction(
-        move |conn| -> Result<(Todo, Vec<Todo>, Project), diesel::result::Error> {
+    let (todo, todo_children, project, blocker, parent) =
+        conn.transaction(move |conn| -> Result<ViewedTodo, diesel::result::Error> {
             let todo = todos_dsl::todos
                 .filter(todos_dsl::id.eq(view_todo_id))
                 .first::<Todo>(conn)?;
+            let parent = match todo.parent_id {
+                Some(parent_id) => todos_dsl::todos
+                    .filter(todos_dsl::id.eq(parent_id))
+                    .first::<Todo>(conn)
+                    .optional()?,
+                None => None,
+            };
+            let blocker = match todo.blocked_by_id {
+                Some(blocked_by_id) if todo.status == TodoStatus::Blocked => todos_dsl::todos
+                    .filter(todos_dsl::id.eq(blocked_by_id))
+                    .first::<Todo>(conn)
+                    .optional()?,
+                _ => None,
+            };
             let direct_children = todos_dsl::todos
                 .filter(todos_dsl::parent_id.eq(view_todo_id))
                 .load::<Todo>(conn)?;
-            let project = projects_dsl::projects
+            // The todo was asked for directly, so show its project whatever its status
+            let project = ProjectQuery::new()
+                .include_inactive()
+                .build()
                 .filter(projects_dsl::id.eq(todo.project_id))
                 .first::<Project>(conn)?;
-            Ok((todo, direct_children, project))
-        },
-    )?;
+            Ok((todo, direct_children, project, blocker, parent))
+        })?;
+
+    let mut writer = output_writer(output)?;
+    if format == OutputFormat::Minimal {
+        writeln!(writer, "{}", todo.to_oneliner())?;
+        if let Some(path) = output {
+            output_written_message(path);
+        }
+        return Ok(());
+    }
+
+    if debug_display {
+        todo.to_debug_message().display_to_writer(&mut writer)?;
+        for child in todo_children {
+            child.to_debug_message().display_to_writer(&mut writer)?;
+        }
+        if let Some(path) = output {
+            output_written_message(path);
+        }
+        return Ok(());
+    }
 
-    project.to_message(None).display();
-    todo.to_detailed_message(None).display();
+    if todo.parent_id.is_some() {
+        writeln!(
+            writer,
+            "{}",
+            todo_breadcrumb(&project, parent.as_ref(), &todo)
+        )?;
+    }
+    project.to_message(None).display_to_writer(&mut writer)?;
+    todo.to_detailed_message_with_blocker(blocker.as_ref(), None)
+        .display_to_writer(&mut writer)?;
     for child in todo_children {
-        child.to_detailed_message(None).display();
+        child
+            .to_detailed_message(None)
+            .display_to_writer(&mut writer)?;
     }
 
+    if let Some(path) = output {
+        output_written_message(path);
+    }
     Ok(())
 }
 
 pub fn list_todo_command() -> Command {
-    Command::new("todo").args([
-        TuduArg::Priority.into_arg(true),
-        TuduArg::IncludeDone.into_arg(true),
-    ])
+    Command::new("todo")
+        .args(TuduArg::IncludeDone.into_negatable_args(true))
+        .args([
+            TuduArg::Priority.into_arg(true).action(ArgAction::Append),
+            TuduArg::Status
+                .into_arg(true)
+                .default_value(None::<&str>)
+                .action(ArgAction::Append),
+            TuduArg::Output.into_arg(true),
+            TuduArg::Assignee
+                .into_arg(true)
+                .conflicts_with(TuduArg::AssignedToMe.name()),
+            TuduArg::AssignedToMe.into_arg(true),
+            TuduArg::Tag.into_arg(true),
+            TuduArg::ShowIdOnly.into_arg(true),
+            TuduArg::SinceCreated.into_arg(true),
+            TuduArg::OlderThan.into_arg(true),
+            TuduArg::Format.into_arg(true),
+            TuduArg::Week
+                .into_arg(true)
+                .conflicts_with(TuduArg::ShowIdOnly.name()),
+            TuduArg::StartDate
+                .into_arg(true)
+                .requires(TuduArg::Week.name()),
+            TuduArg::Days.into_arg(true).requires(TuduArg::Week.name()),
+            TuduArg::BlockedBy.into_arg(true),
+            TuduArg::OverdueFirst.into_arg(true),
+            TuduArg::Overdue.into_arg(true),
+            TuduArg::GroupByDay
+                .into_arg(true)
+                .conflicts_with_all([TuduArg::Week.name(), TuduArg::ShowIdOnly.name()]),
+            TuduArg::ProjectId.into_arg(true),
+            TuduArg::EstimatePerDay.into_arg(true),
+            TuduArg::WithUrl
+                .into_arg(true)
+                .conflicts_with(TuduArg::WithoutUrl.name()),
+            TuduArg::WithoutUrl.into_arg(true),
+            TuduArg::WithDescription
+                .into_arg(true)
+                .conflicts_with(TuduArg::WithoutDescription.name()),
+            TuduArg::WithoutDescription.into_arg(true),
+            TuduArg::WithLocation
+                .into_arg(true)
+                .conflicts_with(TuduArg::WithoutLocation.name()),
+            TuduArg::WithoutLocation.into_arg(true),
+            TuduArg::DueBefore.into_arg(true),
+            TuduArg::DueAfter.into_arg(true),
+            TuduArg::SortBy.into_arg(true),
+            TuduArg::Descending.into_arg(true),
+            TuduArg::Limit.into_arg(true),
+            TuduArg::Offset.into_arg(true),
+        ])
 }
 
+/// The field `list todo --sort_by` orders the todos by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SortField {
+    /// From low to urgent.
+    #[default]
+    Priority,
+    /// Soonest first, todos without a due date last.
+    DueDate,
+    /// Oldest first.
+    CreatedAt,
+    /// In the order of the status values, from to-do to cancelled.
+    Status,
+    /// Alphabetically.
+    Title,
+}
+
+/// The window of days shown by `--week`.
+struct CalendarWindow {
+    start: NaiveDate,
+    days: usize,
+}
+
+#[derive(Default)]
 struct ListTodoFilters {
-    priority: TodoPriority,
+    priorities: Vec<TodoPriority>,
+    statuses: Vec<TodoStatus>,
     include_done: bool,
+    output: Option<PathBuf>,
+    assignee: Option<String>,
+    tag: Option<String>,
+    show_id_only: bool,
+    since_created: Option<i64>,
+    older_than: Option<i64>,
+    format: OutputFormat,
+    week: Option<CalendarWindow>,
+    blocked_by: Option<i32>,
+    overdue_first: bool,
+    overdue: bool,
+    group_by_day: bool,
+    project_id: Option<ProjectId>,
+    estimate_per_day: bool,
+    has_url: Option<bool>,
+    has_description: Option<bool>,
+    has_location: Option<bool>,
+    due_before: Option<NaiveDateTime>,
+    due_after: Option<NaiveDateTime>,
+    sort_by: SortField,
+    descending: bool,
+    limit: i64,
+    offset: i64,
+}
+
+// The project to scope the listing to: the flag, else the config like `new todo`, else none.
+// Unlike `new todo`, a lone active project isn't picked so the listing still spans every project
+fn parse_optional_project_id(matches: &ArgMatches) -> Option<ProjectId> {
+    let (project_id, source) = parse_flag_or_config_project_id(matches)?;
+    project_id_source_message(project_id, &source);
+    Some(project_id)
+}
+
+// Describes the priority, status, project, tag, overdue and due date filters in use, e.g. `Filters: priority High • project #2`
+fn active_filter_summary(filters: &ListTodoFilters) -> Option<String> {
+    let join = |values: Vec<String>| values.join(", ");
+    let mut parts = Vec::new();
+    if !filters.priorities.is_empty() {
+        let priorities = filters.priorities.iter().map(|p| p.to_string()).collect();
+        parts.push(format!("priority {}", join(priorities)));
+    }
+    if !filters.statuses.is_empty() {
+        let statuses = filters.statuses.iter().map(|s| s.to_string()).collect();
+        parts.push(format!("status {}", join(statuses)));
+    }
+    if let Some(project_id) = filters.project_id {
+        parts.push(format!("project #{}", project_id));
+    }
+    if let Some(tag) = &filters.tag {
+        parts.push(format!("tag {}", tag));
+    }
+    if filters.overdue {
+        parts.push("overdue".to_string());
+    }
+    if let Some(date) = filters.due_after {
+        parts.push(format!("due after {}", display_datetime(date)));
+    }
+    if let Some(date) = filters.due_before {
+        parts.push(format!("due before {}", display_datetime(date)));
+    }
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(format!("Filters: {}", parts.join(" • ")))
+    }
+}
+
+// Reads a `--with_*` / `--without_*` pair into whether the field has to be set, if either was given
+fn parse_presence_filter(matches: &ArgMatches, with: TuduArg, without: TuduArg) -> Option<bool> {
+    if matches.get_flag(with.name()) {
+        Some(true)
+    } else if matches.get_flag(without.name()) {
+        Some(false)
+    } else {
+        None
+    }
 }
 
 fn parse_list_todo_command_matches(matches: &ArgMatches) -> TuduResult<ListTodoFilters> {
-    let priority = matches
-        .get_one(TuduArg::Priority.name())
-        .copied()
+    // The priority arg defaults to low, which filters the same as not passing it at all
+    let priorities: Vec<TodoPriority> = if is_arg_explicit(matches, TuduArg::Priority) {
+        matches
+            .get_many(TuduArg::Priority.name())
+            .map(|values| values.copied().collect())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let statuses: Vec<TodoStatus> = matches
+        .get_many(TuduArg::Status.name())
+        .map(|values| values.copied().collect())
         .unwrap_or_default();
-    let include_done = matches.get_flag(TuduArg::IncludeDone.name());
+    let include_done = get_negatable_flag(matches, TuduArg::IncludeDone, false);
+    let output: Option<&PathBuf> = matches.get_one(TuduArg::Output.name());
+    let assignee = if matches.get_flag(TuduArg::AssignedToMe.name()) {
+        let user = env::tudu_user_env()
+            .ok_or_else(|| TuduError::MissingEnvError("TUDU_USER".to_string()))?;
+        Some(user)
+    } else {
+        matches.get_one::<String>(TuduArg::Assignee.name()).cloned()
+    };
+    let show_id_only = matches.get_flag(TuduArg::ShowIdOnly.name());
+    let since_created: Option<&i64> = matches.get_one(TuduArg::SinceCreated.name());
+    let older_than: Option<&i64> = matches.get_one(TuduArg::OlderThan.name());
+    let week = if matches.get_flag(TuduArg::Week.name()) {
+        let start: Option<&NaiveDate> = matches.get_one(TuduArg::StartDate.name());
+        let days: Option<&u16> = matches.get_one(TuduArg::Days.name());
+        Some(CalendarWindow {
+            start: start
+                .copied()
+                .unwrap_or_else(|| chrono::Local::now().date_naive()),
+            days: days.copied().unwrap_or(7) as usize,
+        })
+    } else {
+        None
+    };
     Ok(ListTodoFilters {
-        priority,
+        priorities,
+        statuses,
         include_done,
+        output: output.cloned(),
+        assignee,
+        tag: matches
+            .get_one::<String>(TuduArg::Tag.name())
+            .map(|tag| tag.trim().to_string()),
+        show_id_only,
+        since_created: since_created.copied(),
+        older_than: older_than.copied(),
+        format: matches
+            .get_one(TuduArg::Format.name())
+            .copied()
+            .unwrap_or_default(),
+        week,
+        blocked_by: matches.get_one(TuduArg::BlockedBy.name()).copied(),
+        overdue_first: matches.get_flag(TuduArg::OverdueFirst.name()),
+        overdue: matches.get_flag(TuduArg::Overdue.name()),
+        group_by_day: matches.get_flag(TuduArg::GroupByDay.name()),
+        project_id: parse_optional_project_id(matches),
+        estimate_per_day: matches.get_flag(TuduArg::EstimatePerDay.name()),
+        has_url: parse_presence_filter(matches, TuduArg::WithUrl, TuduArg::WithoutUrl),
+        has_description: parse_presence_filter(
+            matches,
+            TuduArg::WithDescription,
+            TuduArg::WithoutDescription,
+        ),
+        has_location: parse_presence_filter(
+            matches,
+            TuduArg::WithLocation,
+            TuduArg::WithoutLocation,
+        ),
+        due_before: matches
+            .get_one::<ValidDateTime>(TuduArg::DueBefore.name())
+            .map(|d| d.0),
+        due_after: matches
+            .get_one::<ValidDateTime>(TuduArg::DueAfter.name())
+            .map(|d| d.0),
+        sort_by: matches
+            .get_one(TuduArg::SortBy.name())
+            .copied()
+            .unwrap_or_default(),
+        // Priority descending is the order todos were always listed in without --sort_by
+        descending: matches.get_flag(TuduArg::Descending.name())
+            || matches
+                .get_one::<SortField>(TuduArg::SortBy.name())
+                .is_none(),
+        limit: matches
+            .get_one(TuduArg::Limit.name())
+            .copied()
+            .unwrap_or(DEFAULT_LIMIT),
+        offset: matches
+            .get_one(TuduArg::Offset.name())
+            .copied()
+            .unwrap_or_default(),
     })
 }
 
-pub fn handle_list_todo_command(matches: &ArgMatches) -> TuduResult<()> {
-    let mut connection = database::database_connection();
+/// Spreads the estimated minutes of the open todos over the working days from `today` until the
+/// latest due date among them. `today` is a UTC date, like the stored due dates.
+///
+/// Returns `None` when no open todo has both an estimate and a due date.
+fn estimate_per_day_from(todos: &[Todo], today: NaiveDate) -> Option<f64> {
+    let estimated: Vec<&Todo> = todos
+        .iter()
+        .filter(|todo| !todo.status.is_terminal())
+        .filter(|todo| todo.estimated_minutes.is_some())
+        .collect();
+    let last_due_date = estimated
+        .iter()
+        .filter_map(|todo| todo.due_date)
+        .max()?
+        .date();
+    let total_minutes: i32 = estimated
+        .iter()
+        .filter_map(|todo| todo.estimated_minutes)
+        .sum();
+
+    // Work that is already due still has to be done, so give it at least one day
+    let days = working_days_between(today, last_due_date).max(1);
+    Some(total_minutes as f64 / days as f64)
+}
+
+// Counts the Monday to Friday days from `start` through `end`, ignoring holidays
+fn working_days_between(start: NaiveDate, end: NaiveDate) -> i64 {
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .filter(|date| date.weekday().number_from_monday() <= 5)
+        .count() as i64
+}
+
+/// Buckets todos by the day they are due, for `days` days starting at `start`.
+///
+/// Every day in the window gets a bucket, even when nothing is due. Todos due outside the
+/// window or without a due date are left out.
+fn group_by_day(todos: Vec<Todo>, start: NaiveDate, days: usize) -> Vec<(NaiveDate, Vec<Todo>)> {
+    let mut buckets: Vec<(NaiveDate, Vec<Todo>)> = start
+        .iter_days()
+        .take(days)
+        .map(|date| (date, Vec::new()))
+        .collect();
+
+    for todo in todos {
+        let Some(due_date) = todo.due_date else {
+            continue;
+        };
+        let offset = (due_date.date() - start).num_days();
+        if offset >= 0
+            && let Some((_, bucket)) = buckets.get_mut(offset as usize)
+        {
+            bucket.push(todo);
+        }
+    }
+
+    buckets
+}
+
+/// Todos split up by when they are due, for `--group_by_day`.
+struct DueDayGroups {
+    overdue: Vec<Todo>,
+    days: Vec<(NaiveDate, Vec<Todo>)>,
+    undated: Vec<Todo>,
+}
+
+/// Splits todos into overdue ones, one group per due day in date order, and undated ones.
+///
+/// Every group is sorted by priority, most urgent first.
+fn group_by_due_day(todos: Vec<Todo>) -> DueDayGroups {
+    let mut groups = DueDayGroups {
+        overdue: Vec::new(),
+        days: Vec::new(),
+        undated: Vec::new(),
+    };
+    let mut days: std::collections::BTreeMap<NaiveDate, Vec<Todo>> = Default::default();
+
+    for todo in todos {
+        match todo.due_date {
+            _ if todo.is_overdue() => groups.overdue.push(todo),
+            Some(due_date) => days.entry(due_date.date()).or_default().push(todo),
+            None => groups.undated.push(todo),
+        }
+    }
+    groups.days = days.into_iter().collect();
+
+    let by_priority = |a: &Todo, b: &Todo| b.priority.cmp(&a.priority);
+    groups.overdue.sort_by(by_priority);
+    groups.undated.sort_by(by_priority);
+    for (_, todos) in groups.days.iter_mut() {
+        todos.sort_by(by_priority);
+    }
+    groups
+}
+
+/// The width of the line under the project header of `list todo --project_id`.
+const PROJECT_HEADER_RULE_WIDTH: usize = 40;
+
+/// The most todos `list todo` prints at once without `--limit`.
+const DEFAULT_LIMIT: i64 = 50;
+
+// The `Showing 51-100 of 142 todos` footer, or `None` when every matching todo was shown
+fn pagination_footer(offset: i64, shown: usize, total: i64) -> Option<String> {
+    let shown = shown as i64;
+    if offset == 0 && shown >= total {
+        return None;
+    }
+    if shown == 0 {
+        return Some(format!(
+            "Showing none of {} todos, --offset {} is past the last one.",
+            total, offset
+        ));
+    }
+    Some(format!(
+        "Showing {}-{} of {} todos.",
+        offset + 1,
+        offset + shown,
+        total
+    ))
+}
+
+// The todos matching the list filters. Built fresh for both the count and the data query
+fn filtered_todos_query(
+    filters: &ListTodoFilters,
+    now: NaiveDateTime,
+) -> todos::BoxedQuery<'_, Sqlite> {
+    // A single priority keeps meaning "at least this priority", several are matched exactly
+    let min_priority = match filters.priorities.as_slice() {
+        [priority] => *priority,
+        _ => TodoPriority::Low,
+    };
+    let mut query = todos_dsl::todos
+        .into_boxed()
+        .filter(todos_dsl::priority.ge(min_priority));
+    // Explicit statuses replace the default of hiding done todos
+    if !filters.include_done && filters.statuses.is_empty() {
+        query = query.filter(todos_dsl::status.ne(TodoStatus::Done));
+    }
+    if filters.priorities.len() > 1 {
+        query = query.filter(todos_dsl::priority.eq_any(filters.priorities.clone()));
+    }
+    if !filters.statuses.is_empty() {
+        query = query.filter(todos_dsl::status.eq_any(filters.statuses.clone()));
+    }
+    if let Some(assignee) = &filters.assignee {
+        query = query.filter(todos_dsl::assignee.eq(assignee));
+    }
+    if let Some(blocked_by) = filters.blocked_by {
+        query = query.filter(todos_dsl::blocked_by_id.eq(blocked_by));
+    }
+    if let Some(project_id) = filters.project_id {
+        query = query.filter(todos_dsl::project_id.eq(project_id));
+    }
+    if let Some(tag) = &filters.tag {
+        // Tags are stored as `a,b,c`, so wrapping both sides in commas only matches whole tags
+        let wrapped_tags = ","
+            .into_sql::<Nullable<Text>>()
+            .concat(todos_dsl::tags)
+            .concat(",");
+        query = query.filter(
+            wrapped_tags
+                .like(format!("%,{},%", escape_like(tag)))
+                .escape('\\'),
+        );
+    }
+    match filters.has_url {
+        Some(true) => query = query.filter(todos_dsl::url.is_not_null()),
+        Some(false) => query = query.filter(todos_dsl::url.is_null()),
+        None => {}
+    }
+    match filters.has_description {
+        Some(true) => query = query.filter(todos_dsl::description.is_not_null()),
+        Some(false) => query = query.filter(todos_dsl::description.is_null()),
+        None => {}
+    }
+    match filters.has_location {
+        Some(true) => query = query.filter(todos_dsl::location.is_not_null()),
+        Some(false) => query = query.filter(todos_dsl::location.is_null()),
+        None => {}
+    }
+    // Todos without a created_at never match these comparisons so they are left out
+    if let Some(days) = filters.since_created {
+        query = query.filter(todos_dsl::created_at.gt(now - Duration::days(days)));
+    }
+    if let Some(days) = filters.older_than {
+        query = query.filter(todos_dsl::created_at.lt(now - Duration::days(days)));
+    }
+    // The same rule as `Todo::is_overdue`, so todos without a due date never match
+    if filters.overdue {
+        query = query
+            .filter(todos_dsl::due_date.lt(now))
+            .filter(todos_dsl::status.ne_all([TodoStatus::Done, TodoStatus::Cancelled]));
+    }
+    // Likewise todos without a due date are left out whenever a due date range is given
+    if let Some(date) = filters.due_before {
+        query = query.filter(todos_dsl::due_date.lt(date));
+    }
+    if let Some(date) = filters.due_after {
+        query = query.filter(todos_dsl::due_date.gt(date));
+    }
+    query
+}
+
+// Orders the todos by the `--sort_by` field, with the id as a tie breaker so the order is stable
+fn sorted_todos_query<'a>(
+    query: todos::BoxedQuery<'a, Sqlite>,
+    filters: &ListTodoFilters,
+) -> todos::BoxedQuery<'a, Sqlite> {
+    let query = match (filters.sort_by, filters.descending) {
+        (SortField::Priority, false) => query.order(todos_dsl::priority.asc()),
+        (SortField::Priority, true) => query.order(todos_dsl::priority.desc()),
+        // Todos without a due date go last either way
+        (SortField::DueDate, false) => {
+            query.order((todos_dsl::due_date.is_null(), todos_dsl::due_date.asc()))
+        }
+        (SortField::DueDate, true) => {
+            query.order((todos_dsl::due_date.is_null(), todos_dsl::due_date.desc()))
+        }
+        (SortField::CreatedAt, false) => query.order(todos_dsl::created_at.asc()),
+        (SortField::CreatedAt, true) => query.order(todos_dsl::created_at.desc()),
+        (SortField::Status, false) => query.order(todos_dsl::status.asc()),
+        (SortField::Status, true) => query.order(todos_dsl::status.desc()),
+        (SortField::Title, false) => query.order(todos_dsl::title.asc()),
+        (SortField::Title, true) => query.order(todos_dsl::title.desc()),
+    };
+    query.then_order_by(todos_dsl::id.asc())
+}
+
+// The listed project, the number of matching todos, the todos on this page and, when asked
+// for, the daily workload of every matching todo
+type ListedTodos = (Option<Project>, i64, Vec<Todo>, Option<Option<f64>>);
+
+pub fn handle_list_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    use crate::schema::projects::dsl as projects_dsl;
+
     let filters = parse_list_todo_command_matches(matches)?;
+    let now = chrono::Utc::now().naive_utc();
 
-    let res: Vec<Todo> = connection.transaction(move |conn| {
-        if !filters.include_done {
-            todos_dsl::todos
-                .filter(
-                    todos_dsl::status
-                        .ne(TodoStatus::Done)
-                        .and(todos_dsl::priority.eq(filters.priority))
-                        .or(todos_dsl::priority.gt(filters.priority)),
-                )
-                .load::<Todo>(conn)
+    let (project, total, res, workload) =
+        conn.transaction(|conn| -> Result<ListedTodos, diesel::result::Error> {
+            let project = match filters.project_id {
+                Some(project_id) => Some(
+                    ProjectQuery::new()
+                        .include_inactive()
+                        .build()
+                        .filter(projects_dsl::id.eq(project_id))
+                        .first::<Project>(conn)?,
+                ),
+                None => None,
+            };
+            let total = filtered_todos_query(&filters, now)
+                .count()
+                .get_result::<i64>(conn)?;
+            let todos = sorted_todos_query(filtered_todos_query(&filters, now), &filters)
+                .limit(filters.limit)
+                .offset(filters.offset)
+                .load::<Todo>(conn)?;
+            // The workload covers every matching todo, not only the ones on this page
+            let workload = if filters.estimate_per_day {
+                let all = filtered_todos_query(&filters, now).load::<Todo>(conn)?;
+                Some(estimate_per_day_from(&all, now.date()))
+            } else {
+                None
+            };
+            Ok((project, total, todos, workload))
+        })?;
+
+    // Only create the output file once the todos loaded, so a failure leaves it untouched
+    let mut writer = output_writer(filters.output.as_ref())?;
+
+    // Scripting formats only print the todos themselves
+    let scripting = filters.show_id_only || filters.format == OutputFormat::Minimal;
+    if let Some(project) = &project
+        && !scripting
+    {
+        project.to_message(None).display_to_writer(&mut writer)?;
+        writeln!(writer, "{}", "─".repeat(PROJECT_HEADER_RULE_WIDTH))?;
+    }
+
+    let todo_count = res.len();
+    // Both partitions keep the order the todos were loaded in
+    let (overdue, res): (Vec<Todo>, Vec<Todo>) = if filters.overdue_first {
+        res.into_iter().partition(|todo| todo.is_overdue())
+    } else {
+        (Vec::new(), res)
+    };
+
+    if let Some(window) = &filters.week {
+        let (dated, undated): (Vec<Todo>, Vec<Todo>) = overdue
+            .into_iter()
+            .chain(res)
+            .partition(|todo| todo.due_date.is_some());
+        let days = group_by_day(dated, window.start, window.days);
+        todo_calendar(&days, &undated).display_to_writer(&mut writer)?;
+    } else if filters.group_by_day {
+        let groups = group_by_due_day(overdue.into_iter().chain(res).collect());
+        let mut sections: Vec<(Message, Vec<Todo>)> = Vec::new();
+        if !groups.overdue.is_empty() {
+            let heading = format!("Overdue ({})", groups.overdue.len());
+            sections.push((section_heading(heading), groups.overdue));
+        }
+        for (date, todos) in groups.days {
+            sections.push((group_heading(format_day_heading(date)), todos));
+        }
+        if !groups.undated.is_empty() {
+            sections.push((group_heading("No Due Date".to_string()), groups.undated));
+        }
+        for (heading, todos) in sections {
+            writeln!(writer)?;
+            heading.display_to_writer(&mut writer)?;
+            for todo in todos {
+                todo.to_list_message(None).display_to_writer(&mut writer)?;
+            }
+        }
+    } else if filters.show_id_only {
+        for todo in overdue.iter().chain(res.iter()) {
+            writeln!(writer, "{}", todo.id)?;
+        }
+    } else if filters.format == OutputFormat::Minimal {
+        for todo in overdue.iter().chain(res.iter()) {
+            writeln!(writer, "{}", todo.to_oneliner())?;
+        }
+    } else if filters.format == OutputFormat::Wide {
+        let todos: Vec<Todo> = overdue.into_iter().chain(res).collect();
+        let (headers, rows) = todo_table(&todos);
+        print_table(&mut writer, headers, rows)?;
+    } else {
+        if !overdue.is_empty() {
+            writeln!(writer)?;
+            section_heading("⚠ Overdue".to_string()).display_to_writer(&mut writer)?;
+        }
+        for todo in overdue {
+            writeln!(writer)?;
+            todo.to_list_message(None).display_to_writer(&mut writer)?;
+        }
+        for todo in res {
+            writeln!(writer)?;
+            todo.to_list_message(None).display_to_writer(&mut writer)?;
+        }
+    }
+
+    if let Some(minutes_per_day) = workload
+        && !scripting
+    {
+        writeln!(writer)?;
+        workload_message(minutes_per_day).display_to_writer(&mut writer)?;
+    }
+
+    if todo_count == 0 && total == 0 && !scripting {
+        info_message("No todos found matching your filters.");
+        if let Some(summary) = active_filter_summary(&filters) {
+            info_message(&summary);
+        }
+        // Done todos are never overdue, so including them wouldn't help
+        if !filters.include_done && filters.statuses.is_empty() && !filters.overdue {
+            info_message("Try --include_done to include completed todos.");
+        }
+    }
+
+    if let Some(days) = filters.older_than
+        && todo_count > 0
+    {
+        info_message(&format!(
+            "These {} todos haven't been updated in over {} days. Consider reviewing them.",
+            todo_count, days
+        ));
+    }
+
+    if let Some(footer) = pagination_footer(filters.offset, todo_count, total)
+        && !scripting
+    {
+        // Goes through the writer so it stays below the buffered todos
+        let footer = if filters.offset + (todo_count as i64) < total {
+            format!(
+                "{} See the next page with --offset {}.",
+                footer,
+                filters.offset + filters.limit
+            )
         } else {
+            footer
+        };
+        writeln!(writer)?;
+        footer_message(footer).display_to_writer(&mut writer)?;
+    }
+
+    if let Some(path) = &filters.output {
+        output_written_message(path);
+    }
+    Ok(())
+}
+
+pub fn delete_todo_command() -> Command {
+    Command::new("todo").args([
+        TuduArg::TodoId.into_arg(false).required(true),
+        TuduArg::Yes.into_arg(true),
+    ])
+}
+
+pub fn handle_delete_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let id: i32 = *matches
+        .get_one(TuduArg::TodoId.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+
+    if !matches.get_flag(TuduArg::Yes.name())
+        && !confirm_prompt(&format!("Permanently delete todo #{}?", id))
+    {
+        info_message("Nothing was deleted");
+        return Ok(());
+    }
+
+    let (todo, promoted) = conn.transaction(
+        move |conn| -> Result<(Todo, usize), diesel::result::Error> {
+            let todo = todos_dsl::todos
+                .filter(todos_dsl::id.eq(id))
+                .first::<Todo>(conn)?;
+            // Foreign keys aren't enforced, so subtodos would be left pointing at a deleted
+            // parent. Make them top-level first
+            let promoted = update(todos_dsl::todos.filter(todos_dsl::parent_id.eq(id)))
+                .set(todos_dsl::parent_id.eq(None::<i32>))
+                .execute(conn)?;
+            update(todos_dsl::todos.filter(todos_dsl::blocked_by_id.eq(id)))
+                .set(todos_dsl::blocked_by_id.eq(None::<i32>))
+                .execute(conn)?;
+            delete(todos_dsl::todos.filter(todos_dsl::id.eq(id))).execute(conn)?;
+            Ok((todo, promoted))
+        },
+    )?;
+
+    todo.to_message(Some(Prefix::Delete)).display();
+    if promoted > 0 {
+        info_message(&format!(
+            "Moved {} subtodos of #{} to the top level",
+            promoted, todo.id
+        ));
+    }
+    Ok(())
+}
+
+pub fn search_todo_command() -> Command {
+    Command::new("todo").args([
+        TuduArg::Query.into_arg(false).required(true),
+        TuduArg::ProjectId.into_arg(true),
+        TuduArg::Status.into_arg(true),
+        TuduArg::Priority.into_arg(true),
+        TuduArg::CaseSensitive.into_arg(true),
+    ])
+}
+
+fn parse_search_todo_command_matches(matches: &ArgMatches) -> TuduResult<SearchTodo> {
+    let query: &String = matches
+        .get_one(TuduArg::Query.name())
+        .ok_or(TuduError::RequiredArgumentError)?;
+    // Status and priority have defaults for the other commands, so only filter when passed
+    let status = match is_arg_explicit(matches, TuduArg::Status) {
+        true => matches
+            .get_one::<TodoStatus>(TuduArg::Status.name())
+            .copied(),
+        false => None,
+    };
+    let priority = match is_arg_explicit(matches, TuduArg::Priority) {
+        true => matches
+            .get_one::<TodoPriority>(TuduArg::Priority.name())
+            .copied(),
+        false => None,
+    };
+
+    Ok(SearchTodo {
+        query: query.clone(),
+        project_id: matches.get_one(TuduArg::ProjectId.name()).copied(),
+        status,
+        priority,
+        case_sensitive: matches.get_flag(TuduArg::CaseSensitive.name()),
+    })
+}
+
+pub fn handle_search_todo_command(
+    matches: &ArgMatches,
+    conn: &mut SqliteConnection,
+) -> TuduResult<()> {
+    let params = parse_search_todo_command_matches(matches)?;
+    let res = search(conn, params)?;
+
+    for todo in res {
+        Message::new()
+            .with_blank_line()
+            .merge(todo.to_list_message(None))
+            .display();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::database::{
+        in_memory_connection, insert_test_project, insert_test_todo, test_new_todo,
+    };
+
+    fn parse_due_date(due_date: Option<&str>) -> Option<chrono::NaiveDateTime> {
+        due_date.map(|d| chrono::NaiveDateTime::parse_from_str(d, "%Y-%m-%d %H:%M").unwrap())
+    }
+
+    fn create_due_test_todo(id: i32, due_date: Option<&str>) -> Todo {
+        Todo {
+            id,
+            project_id: 1,
+            parent_id: None,
+            title: format!("Task {}", id),
+            description: None,
+            status: TodoStatus::ToDo,
+            priority: TodoPriority::Low,
+            due_date: parse_due_date(due_date),
+            estimated_minutes: None,
+            location: None,
+            url: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            assignee: None,
+            blocked_by_id: None,
+            tags: None,
+            actual_minutes: None,
+        }
+    }
+
+    fn create_estimated_test_todo(id: i32, due_date: Option<&str>, minutes: Option<i32>) -> Todo {
+        let mut todo = create_due_test_todo(id, due_date);
+        todo.estimated_minutes = minutes;
+        todo
+    }
+
+    #[test]
+    fn test_working_days_between() {
+        // 2026-10-12 is a Monday
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        assert_eq!(working_days_between(monday, monday), 1);
+        assert_eq!(
+            working_days_between(monday, NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()),
+            5
+        );
+        assert_eq!(
+            working_days_between(monday, NaiveDate::from_ymd_opt(2026, 10, 20).unwrap()),
+            7
+        );
+        assert_eq!(
+            working_days_between(monday, NaiveDate::from_ymd_opt(2026, 10, 11).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_estimate_per_day() {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let mut done = create_estimated_test_todo(3, Some("2026-10-30 12:00"), Some(600));
+        done.status = TodoStatus::Done;
+        let todos = vec![
+            create_estimated_test_todo(1, Some("2026-10-16 12:00"), Some(300)),
+            create_estimated_test_todo(2, None, Some(300)),
+            create_estimated_test_todo(4, Some("2026-11-30 12:00"), None),
+            done,
+        ];
+
+        assert_eq!(estimate_per_day_from(&todos, monday), Some(120.0));
+    }
+
+    #[test]
+    fn test_estimate_per_day_without_due_dates() {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let todos = vec![create_estimated_test_todo(1, None, Some(30))];
+
+        assert_eq!(estimate_per_day_from(&todos, monday), None);
+        assert_eq!(estimate_per_day_from(&[], monday), None);
+    }
+
+    #[test]
+    fn test_group_by_day() {
+        let start = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let todos = vec![
+            create_due_test_todo(1, Some("2025-06-02 09:00")),
+            create_due_test_todo(2, Some("2025-06-04 23:59")),
+            create_due_test_todo(3, Some("2025-06-02 18:30")),
+            create_due_test_todo(4, Some("2025-06-01 12:00")),
+            create_due_test_todo(5, Some("2025-06-09 12:00")),
+            create_due_test_todo(6, None),
+        ];
+
+        let days = group_by_day(todos, start, 7);
+
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0].0, start);
+        assert_eq!(days[6].0, NaiveDate::from_ymd_opt(2025, 6, 8).unwrap());
+        let ids = |index: usize| days[index].1.iter().map(|t| t.id).collect::<Vec<i32>>();
+        assert_eq!(ids(0), vec![1, 3]);
+        assert_eq!(ids(1), Vec::<i32>::new());
+        assert_eq!(ids(2), vec![2]);
+        assert_eq!(days.iter().map(|(_, t)| t.len()).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_filtered_todos_query_hides_done_at_every_priority() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Filters");
+        for (title, priority, status, tags) in [
+            (
+                "open low",
+                TodoPriority::Low,
+                TodoStatus::ToDo,
+                Some("homework,errand"),
+            ),
+            ("done low", TodoPriority::Low, TodoStatus::Done, None),
+            (
+                "done urgent",
+                TodoPriority::Urgent,
+                TodoStatus::Done,
+                Some("work"),
+            ),
+            (
+                "open urgent",
+                TodoPriority::Urgent,
+                TodoStatus::InProgress,
+                Some("urgent,work"),
+            ),
+        ] {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    status,
+                    priority,
+                    tags: tags.map(str::to_string),
+                    ..test_new_todo(project.id, title)
+                },
+            );
+        }
+        let now = chrono::Utc::now().naive_utc();
+        let titles = |filters: &ListTodoFilters, conn: &mut SqliteConnection| {
+            let mut titles: Vec<String> = filtered_todos_query(filters, now)
+                .select(todos_dsl::title)
+                .load(conn)
+                .unwrap();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(
+            titles(&ListTodoFilters::default(), &mut conn),
+            vec!["open low", "open urgent"]
+        );
+
+        let high = ListTodoFilters {
+            priorities: vec![TodoPriority::High],
+            ..Default::default()
+        };
+        assert_eq!(titles(&high, &mut conn), vec!["open urgent"]);
+
+        // Whole tags only, so `work` doesn't match `homework`
+        let work = ListTodoFilters {
+            tag: Some("work".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(titles(&work, &mut conn), vec!["open urgent"]);
+
+        let high_with_done = ListTodoFilters {
+            priorities: vec![TodoPriority::High],
+            include_done: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            titles(&high_with_done, &mut conn),
+            vec!["done urgent", "open urgent"]
+        );
+
+        // Asking for done todos shows them without --include_done
+        let done = ListTodoFilters {
+            statuses: vec![TodoStatus::Done],
+            ..Default::default()
+        };
+        assert_eq!(titles(&done, &mut conn), vec!["done low", "done urgent"]);
+
+        let in_progress = ListTodoFilters {
+            statuses: vec![TodoStatus::InProgress],
+            ..Default::default()
+        };
+        assert_eq!(titles(&in_progress, &mut conn), vec!["open urgent"]);
+    }
+
+    #[test]
+    fn test_parse_close_todo_only_accepts_closed_statuses() {
+        let parse = |status: &str| {
+            let matches = close_todo_command()
+                .try_get_matches_from(["todo", "1", "--status", status])
+                .unwrap();
+            parse_close_todo_command_matches(&matches).map(|(close_todo, ..)| close_todo.status)
+        };
+
+        assert_eq!(parse("done").unwrap(), TodoStatus::Done);
+        assert_eq!(parse("cancelled").unwrap(), TodoStatus::Cancelled);
+        assert!(matches!(
+            parse("in-progress"),
+            Err(TuduError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_view_todo_format_excludes_wide() {
+        let format = |value: &str| {
+            view_todo_command()
+                .try_get_matches_from(["todo", "1", "--format", value])
+                .map(|matches| {
+                    *matches
+                        .get_one::<OutputFormat>(TuduArg::Format.name())
+                        .unwrap()
+                })
+        };
+
+        assert_eq!(format("minimal").unwrap(), OutputFormat::Minimal);
+        assert!(format("wide").is_err());
+    }
+
+    #[test]
+    fn test_description_with_note() {
+        let completed_at = NaiveDate::from_ymd_opt(2025, 6, 2)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        assert_eq!(
+            description_with_note(None, "Shipped it", completed_at),
+            "Completed: Shipped it (2025-06-02 09:30)"
+        );
+        assert_eq!(
+            description_with_note(Some("Fix the form".to_string()), "Shipped it", completed_at),
+            "Fix the form\n\nCompleted: Shipped it (2025-06-02 09:30)"
+        );
+    }
+
+    #[test]
+    fn test_reopen_todo_clears_completion() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Reopen");
+        let todo = insert_test_todo(
+            &mut conn,
+            NewTodo {
+                status: TodoStatus::Done,
+                completed_at: Some(chrono::Utc::now().naive_utc()),
+                ..test_new_todo(project.id, "Task 1")
+            },
+        );
+        let matches = |id: i32| {
+            reopen_todo_command()
+                .try_get_matches_from(["todo", id.to_string().as_str()])
+                .unwrap()
+        };
+
+        handle_reopen_todo_command(&matches(todo.id), &mut conn).unwrap();
+        let reopened = todos_dsl::todos
+            .filter(todos_dsl::id.eq(todo.id))
+            .first::<Todo>(&mut conn)
+            .unwrap();
+        assert_eq!(reopened.status, TodoStatus::ToDo);
+        assert_eq!(reopened.completed_at, None);
+
+        assert!(matches!(
+            handle_reopen_todo_command(&matches(todo.id), &mut conn),
+            Err(TuduError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_update_todo_clear_description() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Clear");
+        let todo = insert_test_todo(
+            &mut conn,
+            NewTodo {
+                description: Some("Notes".to_string()),
+                ..test_new_todo(project.id, "Task 1")
+            },
+        );
+        let id = todo.id.to_string();
+        let mut update_with = |args: &[&str]| {
+            let matches = update_todo_command()
+                .try_get_matches_from(["todo", id.as_str()].iter().chain(args))
+                .unwrap();
+            handle_update_todo_command(&matches, &mut conn).unwrap();
             todos_dsl::todos
-                .filter(
-                    todos_dsl::priority
-                        .eq(filters.priority)
-                        .or(todos_dsl::priority.gt(filters.priority)),
+                .filter(todos_dsl::id.eq(todo.id))
+                .select(todos_dsl::description)
+                .first::<Option<String>>(&mut conn)
+                .unwrap()
+        };
+
+        assert_eq!(
+            update_with(&["--title", "Renamed"]),
+            Some("Notes".to_string())
+        );
+        assert_eq!(update_with(&["--clear_description"]), None);
+
+        assert!(
+            update_todo_command()
+                .try_get_matches_from(["todo", "1", "--description", "x", "--clear_description"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_filtered_todos_query_tag_wildcards_are_literal() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Tags");
+        for (id, tags) in [(1, "work,home"), (2, "w_rk"), (3, "50%")] {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    tags: Some(tags.to_string()),
+                    ..test_new_todo(project.id, &format!("Task {}", id))
+                },
+            );
+        }
+        let now = chrono::Utc::now().naive_utc();
+        let titles = |tag: &str, conn: &mut SqliteConnection| -> Vec<String> {
+            let filters = ListTodoFilters {
+                tag: Some(tag.to_string()),
+                ..Default::default()
+            };
+            filtered_todos_query(&filters, now)
+                .select(todos_dsl::title)
+                .load(conn)
+                .unwrap()
+        };
+
+        assert_eq!(titles("work", &mut conn), vec!["Task 1"]);
+        assert_eq!(titles("w_rk", &mut conn), vec!["Task 2"]);
+        assert_eq!(titles("%", &mut conn), Vec::<String>::new());
+        assert_eq!(titles("50%", &mut conn), vec!["Task 3"]);
+    }
+
+    #[test]
+    fn test_filtered_todos_query_due_date_range() {
+        let date = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let filters = ListTodoFilters {
+            due_after: Some(date("2024-03-11 00:00")),
+            due_before: Some(date("2024-03-18 00:00")),
+            ..Default::default()
+        };
+        let now = date("2024-03-13 10:30");
+        let sql =
+            diesel::debug_query::<Sqlite, _>(&filtered_todos_query(&filters, now)).to_string();
+        assert!(sql.contains("`todos`.`due_date` < ?"));
+        assert!(sql.contains("`todos`.`due_date` > ?"));
+
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Due");
+        for (id, due_date) in [
+            (1, Some("2024-03-10 09:00")),
+            (2, Some("2024-03-14 09:00")),
+            (3, Some("2024-03-20 09:00")),
+            (4, None),
+        ] {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    due_date: parse_due_date(due_date),
+                    ..test_new_todo(project.id, &format!("Task {}", id))
+                },
+            );
+        }
+        let titles = |filters: &ListTodoFilters, conn: &mut SqliteConnection| {
+            let mut titles: Vec<String> = filtered_todos_query(filters, now)
+                .select(todos_dsl::title)
+                .load(conn)
+                .unwrap();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(titles(&filters, &mut conn), vec!["Task 2"]);
+        let before = ListTodoFilters {
+            due_before: Some(date("2024-03-18 00:00")),
+            ..Default::default()
+        };
+        assert_eq!(titles(&before, &mut conn), vec!["Task 1", "Task 2"]);
+    }
+
+    #[test]
+    fn test_sorted_todos_query_by_due_date() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Sorted");
+        for (id, due_date) in [
+            (1, Some("2024-03-20 09:00")),
+            (2, None),
+            (3, Some("2024-03-10 09:00")),
+            (4, Some("2024-03-14 09:00")),
+        ] {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    due_date: parse_due_date(due_date),
+                    ..test_new_todo(project.id, &format!("Task {}", id))
+                },
+            );
+        }
+        let now = chrono::Utc::now().naive_utc();
+        let titles = |matches: ArgMatches, conn: &mut SqliteConnection| -> Vec<String> {
+            let filters = parse_list_todo_command_matches(&matches).unwrap();
+            sorted_todos_query(filtered_todos_query(&filters, now), &filters)
+                .select(todos_dsl::title)
+                .load(conn)
+                .unwrap()
+        };
+        let matches = |args: &[&str]| {
+            list_todo_command()
+                .try_get_matches_from(
+                    ["todo", "--project_id", &project.id.to_string()]
+                        .iter()
+                        .chain(args),
                 )
-                .load::<Todo>(conn)
+                .unwrap()
+        };
+
+        assert_eq!(
+            titles(matches(&["--sort_by", "due-date"]), &mut conn),
+            vec!["Task 3", "Task 4", "Task 1", "Task 2"]
+        );
+        assert_eq!(
+            titles(
+                matches(&["--sort_by", "due-date", "--descending"]),
+                &mut conn
+            ),
+            vec!["Task 1", "Task 4", "Task 3", "Task 2"]
+        );
+    }
+
+    #[test]
+    fn test_filtered_todos_query_overdue() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Overdue");
+        for (id, due_date, status) in [
+            (1, Some("2024-03-10 09:00"), TodoStatus::ToDo),
+            (2, Some("2024-03-10 09:00"), TodoStatus::Done),
+            (3, Some("2024-03-10 09:00"), TodoStatus::Cancelled),
+            (4, Some("2024-03-20 09:00"), TodoStatus::InProgress),
+            (5, None, TodoStatus::ToDo),
+        ] {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    status,
+                    due_date: parse_due_date(due_date),
+                    ..test_new_todo(project.id, &format!("Task {}", id))
+                },
+            );
         }
-    })?;
+        let now =
+            chrono::NaiveDateTime::parse_from_str("2024-03-13 10:30", "%Y-%m-%d %H:%M").unwrap();
+        let filters = ListTodoFilters {
+            overdue: true,
+            include_done: true,
+            ..Default::default()
+        };
 
-    for todo in res {
-        println!();
-        todo.to_message(None).display();
+        let titles: Vec<String> = filtered_todos_query(&filters, now)
+            .select(todos_dsl::title)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(titles, vec!["Task 1"]);
+    }
+
+    #[test]
+    fn test_duplicate_todo_resets_status_and_timestamps() {
+        let mut conn = in_memory_connection();
+        let project = insert_test_project(&mut conn, "Duplicate");
+        let source = insert_test_todo(
+            &mut conn,
+            NewTodo {
+                status: TodoStatus::Done,
+                due_date: parse_due_date(Some("2024-03-10 09:00")),
+                completed_at: Some(chrono::Utc::now().naive_utc()),
+                tags: Some("weekly".to_string()),
+                ..test_new_todo(project.id, "Task 1")
+            },
+        );
+        let matches = duplicate_todo_command()
+            .try_get_matches_from(["todo", source.id.to_string().as_str(), "--title", "Again"])
+            .unwrap();
+
+        handle_duplicate_todo_command(&matches, &mut conn).unwrap();
+
+        let copy = todos_dsl::todos
+            .order(todos_dsl::id.desc())
+            .first::<Todo>(&mut conn)
+            .unwrap();
+        assert_ne!(copy.id, source.id);
+        assert_eq!(copy.title, "Again");
+        assert_eq!(copy.status, TodoStatus::ToDo);
+        assert_eq!(copy.completed_at, None);
+        assert_eq!(copy.due_date, source.due_date);
+        assert_eq!(copy.tags, source.tags);
+    }
+
+    #[test]
+    fn test_move_todo_takes_subtodos_along() {
+        let mut conn = in_memory_connection();
+        let from = insert_test_project(&mut conn, "From");
+        let to = insert_test_project(&mut conn, "To");
+        let mut insert_todo = |parent_id: Option<TodoId>| {
+            insert_test_todo(
+                &mut conn,
+                NewTodo {
+                    parent_id,
+                    ..test_new_todo(from.id, "Task 1")
+                },
+            )
+        };
+        let parent = insert_todo(None);
+        let child = insert_todo(Some(TodoId(parent.id)));
+        insert_todo(Some(TodoId(parent.id)));
+        let other = insert_todo(None);
+
+        let (moved, subtodos) = move_todo(&mut conn, TodoId(parent.id), ProjectId(to.id)).unwrap();
+        assert_eq!(moved.project_id, to.id);
+        assert_eq!(subtodos, 2);
+        let project_of = |conn: &mut SqliteConnection, id: i32| {
+            todos_dsl::todos
+                .filter(todos_dsl::id.eq(id))
+                .select(todos_dsl::project_id)
+                .first::<i32>(conn)
+                .unwrap()
+        };
+        assert_eq!(project_of(&mut conn, child.id), to.id);
+        assert_eq!(project_of(&mut conn, other.id), from.id);
+
+        for (id, project_id) in [(child.id, from.id), (parent.id, to.id)] {
+            assert!(matches!(
+                move_todo(&mut conn, TodoId(id), ProjectId(project_id)),
+                Err(TuduError::ValidationError(_))
+            ));
+        }
+        assert!(move_todo(&mut conn, TodoId(other.id), ProjectId(999)).is_err());
+    }
+
+    #[test]
+    fn test_pagination_footer() {
+        assert_eq!(pagination_footer(0, 12, 12), None);
+        assert_eq!(
+            pagination_footer(0, 50, 142),
+            Some("Showing 1-50 of 142 todos.".to_string())
+        );
+        assert_eq!(
+            pagination_footer(100, 42, 142),
+            Some("Showing 101-142 of 142 todos.".to_string())
+        );
+        assert_eq!(
+            pagination_footer(200, 0, 142),
+            Some("Showing none of 142 todos, --offset 200 is past the last one.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_list_todo_limit_and_offset() {
+        let parse = |args: &[&str]| {
+            let matches = list_todo_command()
+                .try_get_matches_from(["todo"].iter().chain(args))
+                .unwrap();
+            let filters = parse_list_todo_command_matches(&matches).unwrap();
+            (filters.limit, filters.offset)
+        };
+
+        assert_eq!(parse(&[]), (DEFAULT_LIMIT, 0));
+        assert_eq!(parse(&["--limit", "10", "--offset", "20"]), (10, 20));
+        assert!(
+            list_todo_command()
+                .try_get_matches_from(["todo", "--limit", "0"])
+                .is_err()
+        );
     }
-    Ok(())
 }
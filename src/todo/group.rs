@@ -1,10 +1,10 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::todo::sql::Todo;
 
 /// Represents a hierarchical group of todos, with a main todo and its sub-todos.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TodoGroup {
     /// The main todo item that acts as the parent.
     pub main_todo: Todo,
@@ -25,12 +25,32 @@ impl TodoGroup {
     pub fn add_subtodo(&mut self, subtodo: Todo) {
         self.subtodos.push(subtodo);
     }
+
+    /// Sums the estimated minutes of the main todo and all sub-todos.
+    ///
+    /// Todos without an estimate are skipped. Returns `None` only when none of them have one.
+    pub fn estimated_total_minutes(&self) -> Option<i32> {
+        std::iter::once(&self.main_todo)
+            .chain(self.subtodos.iter())
+            .filter_map(|todo| todo.estimated_minutes)
+            .reduce(|total, minutes| total + minutes)
+    }
+
+    /// Sums the actual minutes of the main todo and all sub-todos.
+    ///
+    /// Todos without an actual time are skipped. Returns `None` only when none of them have one.
+    pub fn actual_total_minutes(&self) -> Option<i32> {
+        std::iter::once(&self.main_todo)
+            .chain(self.subtodos.iter())
+            .filter_map(|todo| todo.actual_minutes)
+            .reduce(|total, minutes| total + minutes)
+    }
 }
 
 /// Organizes a flat list of `Todo` items into a hierarchical structure of `TodoGroup`s.
 ///
-/// Main todos are sorted by priority (descending) and then by status. Sub-todos within
-/// each group are also sorted similarly.
+/// Main todos are sorted by priority (descending), then by status and then by id, so older
+/// todos come first within the same tier. Sub-todos within each group are sorted similarly.
 pub fn organize_todos_hierarchically(todos: Vec<Todo>) -> Vec<TodoGroup> {
     let mut main_todos = Vec::new();
     let mut subtodos_map: HashMap<i32, Vec<Todo>> = HashMap::new();
@@ -40,10 +60,7 @@ pub fn organize_todos_hierarchically(todos: Vec<Todo>) -> Vec<TodoGroup> {
         if todo.parent_id.is_none() {
             main_todos.push(todo);
         } else if let Some(parent_id) = todo.parent_id {
-            subtodos_map
-                .entry(parent_id)
-                .or_insert_with(Vec::new)
-                .push(todo);
+            subtodos_map.entry(parent_id).or_default().push(todo);
         }
     }
 
@@ -52,6 +69,7 @@ pub fn organize_todos_hierarchically(todos: Vec<Todo>) -> Vec<TodoGroup> {
         b.priority
             .cmp(&a.priority)
             .then_with(|| a.status.cmp(&b.status))
+            .then_with(|| a.id.cmp(&b.id))
     });
 
     // Sort subtodos within each group
@@ -60,6 +78,7 @@ pub fn organize_todos_hierarchically(todos: Vec<Todo>) -> Vec<TodoGroup> {
             b.priority
                 .cmp(&a.priority)
                 .then_with(|| a.status.cmp(&b.status))
+                .then_with(|| a.id.cmp(&b.id))
         });
     }
 
@@ -84,10 +103,30 @@ pub fn organize_todos_hierarchically(todos: Vec<Todo>) -> Vec<TodoGroup> {
 #[cfg(test)]
 mod tests {
     use crate::todo::{
-        group::organize_todos_hierarchically,
+        group::{TodoGroup, organize_todos_hierarchically},
         sql::{Todo, TodoPriority, TodoStatus},
     };
 
+    fn create_estimated_test_todo(
+        id: i32,
+        parent_id: Option<i32>,
+        estimated_minutes: Option<i32>,
+    ) -> Todo {
+        let mut todo = create_test_todo(id, parent_id, "Task", TodoPriority::Medium);
+        todo.estimated_minutes = estimated_minutes;
+        todo
+    }
+
+    fn create_actual_test_todo(
+        id: i32,
+        parent_id: Option<i32>,
+        actual_minutes: Option<i32>,
+    ) -> Todo {
+        let mut todo = create_test_todo(id, parent_id, "Task", TodoPriority::Medium);
+        todo.actual_minutes = actual_minutes;
+        todo
+    }
+
     fn create_test_todo(
         id: i32,
         parent_id: Option<i32>,
@@ -109,6 +148,10 @@ mod tests {
             created_at: None,
             updated_at: None,
             completed_at: None,
+            assignee: None,
+            blocked_by_id: None,
+            tags: None,
+            actual_minutes: None,
         }
     }
 
@@ -167,4 +210,77 @@ mod tests {
         assert_eq!(result[1].main_todo.title, "Main Task 2"); // Low priority second
         assert_eq!(result[1].subtodos.len(), 0);
     }
+
+    #[test]
+    fn test_organize_todos_hierarchically_ties_sorted_by_id() {
+        let todos = vec![
+            create_test_todo(4, None, "Task 4", TodoPriority::Medium),
+            create_test_todo(2, None, "Task 2", TodoPriority::Medium),
+            create_test_todo(5, None, "Task 5", TodoPriority::Medium),
+            create_test_todo(1, None, "Task 1", TodoPriority::Medium),
+            create_test_todo(3, None, "Task 3", TodoPriority::Medium),
+            create_test_todo(8, Some(1), "Subtask 8", TodoPriority::Medium),
+            create_test_todo(6, Some(1), "Subtask 6", TodoPriority::Medium),
+            create_test_todo(7, Some(1), "Subtask 7", TodoPriority::Medium),
+        ];
+
+        let result = organize_todos_hierarchically(todos);
+
+        let main_ids: Vec<i32> = result.iter().map(|group| group.main_todo.id).collect();
+        assert_eq!(main_ids, vec![1, 2, 3, 4, 5]);
+        let subtodo_ids: Vec<i32> = result[0].subtodos.iter().map(|todo| todo.id).collect();
+        assert_eq!(subtodo_ids, vec![6, 7, 8]);
+    }
+
+    #[test]
+    fn test_estimated_total_minutes_all_none() {
+        let mut group = TodoGroup::new(create_estimated_test_todo(1, None, None));
+        group.add_subtodo(create_estimated_test_todo(2, Some(1), None));
+
+        assert_eq!(group.estimated_total_minutes(), None);
+    }
+
+    #[test]
+    fn test_estimated_total_minutes_all_some() {
+        let mut group = TodoGroup::new(create_estimated_test_todo(1, None, Some(30)));
+        group.add_subtodo(create_estimated_test_todo(2, Some(1), Some(10)));
+        group.add_subtodo(create_estimated_test_todo(3, Some(1), Some(5)));
+
+        assert_eq!(group.estimated_total_minutes(), Some(45));
+    }
+
+    #[test]
+    fn test_estimated_total_minutes_mixed() {
+        let mut group = TodoGroup::new(create_estimated_test_todo(1, None, None));
+        group.add_subtodo(create_estimated_test_todo(2, Some(1), Some(20)));
+        group.add_subtodo(create_estimated_test_todo(3, Some(1), None));
+
+        assert_eq!(group.estimated_total_minutes(), Some(20));
+    }
+
+    #[test]
+    fn test_actual_total_minutes_all_none() {
+        let mut group = TodoGroup::new(create_actual_test_todo(1, None, None));
+        group.add_subtodo(create_actual_test_todo(2, Some(1), None));
+
+        assert_eq!(group.actual_total_minutes(), None);
+    }
+
+    #[test]
+    fn test_actual_total_minutes_all_some() {
+        let mut group = TodoGroup::new(create_actual_test_todo(1, None, Some(40)));
+        group.add_subtodo(create_actual_test_todo(2, Some(1), Some(15)));
+        group.add_subtodo(create_actual_test_todo(3, Some(1), Some(5)));
+
+        assert_eq!(group.actual_total_minutes(), Some(60));
+    }
+
+    #[test]
+    fn test_actual_total_minutes_mixed() {
+        let mut group = TodoGroup::new(create_actual_test_todo(1, None, None));
+        group.add_subtodo(create_actual_test_todo(2, Some(1), Some(25)));
+        group.add_subtodo(create_actual_test_todo(3, Some(1), None));
+
+        assert_eq!(group.actual_total_minutes(), Some(25));
+    }
 }
@@ -1,16 +1,23 @@
-use crate::schema::todos;
+use crate::{
+    arg::{ProjectId, TodoId},
+    error::TuduResult,
+    schema::todos,
+};
 use chrono::NaiveDateTime;
 use diesel::{
+    BoolExpressionMethods, ExpressionMethods, Insertable, QueryDsl, Queryable, RunQueryDsl,
+    Selectable, SqliteConnection, TextExpressionMethods,
     backend::Backend,
     deserialize::{FromSql, FromSqlRow, Result},
     expression::AsExpression,
+    expression_methods::EscapeExpressionMethods,
     prelude::{AsChangeset, Identifiable},
     serialize::{IsNull, ToSql},
     sql_types::Integer,
     sqlite::Sqlite,
-    Insertable, Queryable, Selectable,
 };
 use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 use strum::{Display, IntoStaticStr};
 
 /// Represents the status of a todo item.
@@ -22,18 +29,21 @@ use strum::{Display, IntoStaticStr};
     Eq,
     PartialOrd,
     Ord,
+    Hash,
     Serialize,
     Deserialize,
     AsExpression,
     FromSqlRow,
     IntoStaticStr,
     Display,
+    Default,
     clap::clap_derive::ValueEnum,
 )]
 #[diesel(sql_type = Integer)]
 #[repr(i32)]
 pub enum TodoStatus {
     /// The task has not been started.
+    #[default]
     ToDo = 0,
     /// The task is actively being worked on.
     InProgress = 1,
@@ -47,12 +57,6 @@ pub enum TodoStatus {
     Cancelled = 5,
 }
 
-impl Default for TodoStatus {
-    fn default() -> Self {
-        Self::ToDo
-    }
-}
-
 impl FromSql<Integer, Sqlite> for TodoStatus {
     fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> Result<Self> {
         let value = i32::from_sql(bytes)?;
@@ -68,6 +72,44 @@ impl FromSql<Integer, Sqlite> for TodoStatus {
     }
 }
 
+impl TodoStatus {
+    /// The hex color used to display the status.
+    pub fn color_hex(&self) -> &'static str {
+        match self {
+            TodoStatus::ToDo => "#CED4DA",
+            TodoStatus::InProgress => "#0D6EFD",
+            TodoStatus::Done => "#198754",
+            TodoStatus::Blocked => "#DC3545",
+            TodoStatus::OnHold => "#FFC107",
+            TodoStatus::Cancelled => "#6C757D",
+        }
+    }
+
+    /// Whether the todo is closed, either as `Done` or `Cancelled`.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TodoStatus::Done | TodoStatus::Cancelled)
+    }
+
+    /// Whether a todo in this status may be moved to `next`.
+    ///
+    /// Keeping the same status is always allowed. Any todo can be moved back to `ToDo`,
+    /// but finished todos have to be reopened before they are worked on again and a
+    /// blocked todo has to be unblocked before it can be done.
+    pub fn can_transition_to(&self, next: TodoStatus) -> bool {
+        use TodoStatus::*;
+        if *self == next {
+            return true;
+        }
+        match self {
+            ToDo | InProgress => true,
+            Blocked => matches!(next, ToDo | InProgress | OnHold | Cancelled),
+            OnHold => matches!(next, ToDo | InProgress | Blocked | Cancelled),
+            Done => matches!(next, ToDo | InProgress),
+            Cancelled => next == ToDo,
+        }
+    }
+}
+
 impl ToSql<Integer, Sqlite> for TodoStatus {
     fn to_sql<'b>(
         &'b self,
@@ -92,6 +134,7 @@ impl ToSql<Integer, Sqlite> for TodoStatus {
     AsExpression,
     FromSqlRow,
     Display,
+    Default,
     clap::clap_derive::ValueEnum,
 )]
 #[diesel(sql_type = Integer)]
@@ -100,6 +143,7 @@ pub enum TodoPriority {
     /// Low priority.
     Low = 0,
     /// Medium priority.
+    #[default]
     Medium = 1,
     /// High priority.
     High = 2,
@@ -107,12 +151,6 @@ pub enum TodoPriority {
     Urgent = 3,
 }
 
-impl Default for TodoPriority {
-    fn default() -> Self {
-        Self::Medium
-    }
-}
-
 impl FromSql<Integer, Sqlite> for TodoPriority {
     fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> Result<Self> {
         let value = i32::from_sql(bytes)?;
@@ -126,6 +164,18 @@ impl FromSql<Integer, Sqlite> for TodoPriority {
     }
 }
 
+impl TodoPriority {
+    /// The hex color used to display the priority.
+    pub fn color_hex(&self) -> &'static str {
+        match self {
+            TodoPriority::Low => "#198754",
+            TodoPriority::Medium => "#0DCAF0",
+            TodoPriority::High => "#FFC107",
+            TodoPriority::Urgent => "#DC3545",
+        }
+    }
+}
+
 impl ToSql<Integer, Sqlite> for TodoPriority {
     fn to_sql<'b>(
         &'b self,
@@ -141,9 +191,9 @@ impl ToSql<Integer, Sqlite> for TodoPriority {
 #[diesel(table_name = todos)]
 pub struct NewTodo {
     /// The ID of the project this todo belongs to.
-    pub project_id: i32,
+    pub project_id: ProjectId,
     /// The ID of the parent todo, if this is a sub-task.
-    pub parent_id: Option<i32>,
+    pub parent_id: Option<TodoId>,
     /// The title of the todo.
     pub title: String,
     /// An optional detailed description of the todo.
@@ -166,6 +216,81 @@ pub struct NewTodo {
     pub updated_at: Option<NaiveDateTime>,
     /// The timestamp when the todo was completed.
     pub completed_at: Option<NaiveDateTime>,
+    /// The person responsible for the todo.
+    pub assignee: Option<String>,
+    /// Comma separated labels, e.g. `work,urgent`.
+    pub tags: Option<String>,
+    /// How long the todo actually took, in minutes.
+    pub actual_minutes: Option<i32>,
+}
+
+impl NewTodo {
+    /// Sets `created_at` and `updated_at` to now instead of relying on the database defaults.
+    pub fn with_default_timestamps(mut self) -> Self {
+        let now = chrono::Utc::now().naive_utc();
+        self.created_at = Some(now);
+        self.updated_at = Some(now);
+        self.completed_at = None;
+        self
+    }
+}
+
+impl Todo {
+    /// The number of whole days since the todo was created, if the creation time is known.
+    pub fn age_in_days(&self) -> Option<i64> {
+        let created_at = self.created_at?;
+        Some((chrono::Utc::now().naive_utc() - created_at).num_days())
+    }
+
+    /// Whether the todo is past its due date and still open.
+    pub fn is_overdue(&self) -> bool {
+        let open = !self.status.is_terminal();
+        open && self
+            .due_date
+            .is_some_and(|due_date| due_date < chrono::Utc::now().naive_utc())
+    }
+
+    /// The tags of the todo in the order they were given, empty when it has none.
+    pub fn tag_list(&self) -> Vec<&str> {
+        self.tags
+            .as_deref()
+            .map(|tags| tags.split(',').filter(|tag| !tag.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The todo as a JSON value, for exports and integrations.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("Todo serialization should never fail")
+    }
+
+    /// The todo as pretty printed JSON.
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Todo serialization should never fail")
+    }
+}
+
+impl From<Todo> for NewTodo {
+    /// Copies an existing todo into a fresh, open todo with unset timestamps.
+    fn from(todo: Todo) -> Self {
+        Self {
+            project_id: ProjectId(todo.project_id),
+            parent_id: todo.parent_id.map(TodoId),
+            title: todo.title,
+            description: todo.description,
+            status: TodoStatus::ToDo,
+            priority: todo.priority,
+            due_date: todo.due_date,
+            estimated_minutes: todo.estimated_minutes,
+            location: todo.location,
+            url: todo.url,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            assignee: todo.assignee,
+            tags: todo.tags,
+            actual_minutes: None,
+        }
+    }
 }
 
 /// Represents the changes to be applied to an existing todo item.
@@ -174,15 +299,15 @@ pub struct NewTodo {
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct UpdateTodo {
     /// The unique identifier of the todo to update.
-    pub id: i32,
+    pub id: TodoId,
     /// The new project ID, if moving the todo.
-    pub project_id: Option<i32>,
+    pub project_id: Option<ProjectId>,
     /// The new parent ID, if changing its hierarchy.
-    pub parent_id: Option<i32>,
+    pub parent_id: Option<TodoId>,
     /// The new title for the todo.
     pub title: Option<String>,
-    /// The new description for the todo.
-    pub description: Option<String>,
+    /// The new description for the todo: `Some(None)` clears it, `None` leaves it unchanged.
+    pub description: Option<Option<String>>,
     /// The new status for the todo.
     pub status: Option<TodoStatus>,
     /// The new priority for the todo.
@@ -199,6 +324,14 @@ pub struct UpdateTodo {
     pub updated_at: NaiveDateTime,
     /// The timestamp when the todo was completed.
     pub completed_at: Option<NaiveDateTime>,
+    /// The new assignee for the todo.
+    pub assignee: Option<String>,
+    /// The todo that is blocking this one.
+    pub blocked_by_id: Option<TodoId>,
+    /// The new comma separated labels for the todo.
+    pub tags: Option<String>,
+    /// The new time the todo actually took, in minutes.
+    pub actual_minutes: Option<i32>,
 }
 
 /// A struct used to mark a todo as closed/completed.
@@ -207,13 +340,17 @@ pub struct UpdateTodo {
 #[diesel(check_for_backend(diesel::sqlite::Sqlite))]
 pub struct CloseTodo {
     /// The unique identifier of the todo to close.
-    pub id: i32,
+    pub id: TodoId,
     /// The timestamp when the todo was last updated.
     pub updated_at: NaiveDateTime,
     /// The timestamp when the todo was completed.
     pub completed_at: NaiveDateTime,
     /// The final status to set for the closed todo (e.g., `Done`).
     pub status: TodoStatus,
+    /// The description with the closing reason appended, if a reason was given.
+    pub description: Option<String>,
+    /// How long the todo actually took, in minutes, if it was given.
+    pub actual_minutes: Option<i32>,
 }
 
 /// Represents a todo item retrieved from the database.
@@ -249,4 +386,220 @@ pub struct Todo {
     pub updated_at: Option<NaiveDateTime>,
     /// The timestamp when the todo was completed.
     pub completed_at: Option<NaiveDateTime>,
-}
\ No newline at end of file
+    /// The person responsible for the todo.
+    pub assignee: Option<String>,
+    /// The ID of the todo that is blocking this one.
+    pub blocked_by_id: Option<i32>,
+    /// Comma separated labels, e.g. `work,urgent`.
+    pub tags: Option<String>,
+    /// How long the todo actually took, in minutes.
+    pub actual_minutes: Option<i32>,
+}
+
+/// Todos are the same todo when they share an id, whatever their other field values.
+impl PartialEq for Todo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Todo {}
+
+impl Hash for Todo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// The parameters of a todo search.
+#[derive(Debug, Default)]
+pub struct SearchTodo {
+    /// The text to look for in the title, description, location and URL.
+    pub query: String,
+    /// Only search todos in this project.
+    pub project_id: Option<i32>,
+    /// Only search todos with this status.
+    pub status: Option<TodoStatus>,
+    /// Only search todos with this priority.
+    pub priority: Option<TodoPriority>,
+    /// Match the letter case of the query exactly.
+    pub case_sensitive: bool,
+}
+
+/// Escapes the LIKE wildcards in `s` with a backslash, so a pattern built from it with
+/// `.escape('\\')` matches it literally.
+pub(crate) fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Matches `query` literally anywhere in a column
+fn like_pattern(query: &str) -> String {
+    format!("%{}%", escape_like(query))
+}
+
+/// Finds the todos whose title, description, location or URL contain the query.
+///
+/// SQLite's `LIKE` ignores case, so case sensitive searches narrow the results down
+/// after they are loaded.
+pub fn search(conn: &mut SqliteConnection, params: SearchTodo) -> TuduResult<Vec<Todo>> {
+    use crate::schema::todos::dsl as todos_dsl;
+
+    let pattern = like_pattern(&params.query);
+    let mut query = todos_dsl::todos
+        .filter(
+            todos_dsl::title
+                .like(&pattern)
+                .escape('\\')
+                .or(todos_dsl::description.like(&pattern).escape('\\'))
+                .or(todos_dsl::location.like(&pattern).escape('\\'))
+                .or(todos_dsl::url.like(&pattern).escape('\\')),
+        )
+        .into_boxed();
+    if let Some(project_id) = params.project_id {
+        query = query.filter(todos_dsl::project_id.eq(project_id));
+    }
+    if let Some(status) = params.status {
+        query = query.filter(todos_dsl::status.eq(status));
+    }
+    if let Some(priority) = params.priority {
+        query = query.filter(todos_dsl::priority.eq(priority));
+    }
+    let mut todos = query.load::<Todo>(conn)?;
+
+    if params.case_sensitive {
+        let contains = |field: &Option<String>| {
+            field
+                .as_ref()
+                .is_some_and(|value| value.contains(&params.query))
+        };
+        todos.retain(|todo| {
+            todo.title.contains(&params.query)
+                || contains(&todo.description)
+                || contains(&todo.location)
+                || contains(&todo.url)
+        });
+    }
+    Ok(todos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::HexColor;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_is_terminal() {
+        assert!(TodoStatus::Done.is_terminal());
+        assert!(TodoStatus::Cancelled.is_terminal());
+        assert!(!TodoStatus::ToDo.is_terminal());
+        assert!(!TodoStatus::Blocked.is_terminal());
+    }
+
+    #[test]
+    fn test_can_transition_to() {
+        assert!(TodoStatus::ToDo.can_transition_to(TodoStatus::Done));
+        assert!(TodoStatus::Done.can_transition_to(TodoStatus::Done));
+        assert!(TodoStatus::Done.can_transition_to(TodoStatus::InProgress));
+        assert!(TodoStatus::Cancelled.can_transition_to(TodoStatus::ToDo));
+        assert!(!TodoStatus::Cancelled.can_transition_to(TodoStatus::InProgress));
+        assert!(!TodoStatus::Blocked.can_transition_to(TodoStatus::Done));
+        assert!(!TodoStatus::Done.can_transition_to(TodoStatus::Blocked));
+    }
+
+    fn create_test_todo() -> Todo {
+        Todo {
+            id: 3,
+            project_id: 1,
+            parent_id: None,
+            title: "Write docs".to_string(),
+            description: None,
+            status: TodoStatus::InProgress,
+            priority: TodoPriority::High,
+            due_date: None,
+            estimated_minutes: Some(30),
+            location: None,
+            url: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            assignee: Some("sam".to_string()),
+            blocked_by_id: None,
+            tags: None,
+            actual_minutes: None,
+        }
+    }
+
+    #[test]
+    fn test_todo_equality_uses_id() {
+        let todo = create_test_todo();
+        let mut renamed = create_test_todo();
+        renamed.title = "Something else".to_string();
+        let mut other = create_test_todo();
+        other.id = 4;
+
+        assert_eq!(todo, renamed);
+        assert_ne!(todo, other);
+
+        let unique: HashSet<Todo> = [todo, renamed, other].into_iter().collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_new_todo_with_default_timestamps() {
+        let mut todo = create_test_todo();
+        todo.completed_at = Some(chrono::Utc::now().naive_utc());
+
+        let new_todo = NewTodo::from(todo).with_default_timestamps();
+
+        assert!(new_todo.created_at.is_some());
+        assert_eq!(new_todo.created_at, new_todo.updated_at);
+        assert_eq!(new_todo.completed_at, None);
+    }
+
+    #[test]
+    fn test_todo_to_json() {
+        let todo = create_test_todo();
+        let json = todo.to_json();
+        assert_eq!(json["id"], 3);
+        assert_eq!(json["title"], "Write docs");
+        assert_eq!(json["estimated_minutes"], 30);
+        assert_eq!(json["assignee"], "sam");
+        assert!(json["due_date"].is_null());
+        assert!(todo.to_json_string().contains("\"title\": \"Write docs\""));
+    }
+
+    #[test]
+    fn test_priority_color_hex_is_valid() {
+        for priority in [
+            TodoPriority::Low,
+            TodoPriority::Medium,
+            TodoPriority::High,
+            TodoPriority::Urgent,
+        ] {
+            assert!(HexColor::from_hex(priority.color_hex()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_status_color_hex_is_valid() {
+        for status in [
+            TodoStatus::ToDo,
+            TodoStatus::InProgress,
+            TodoStatus::Done,
+            TodoStatus::Blocked,
+            TodoStatus::OnHold,
+            TodoStatus::Cancelled,
+        ] {
+            assert!(HexColor::from_hex(status.color_hex()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("login"), "%login%");
+        assert_eq!(like_pattern("100%_done"), "%100\\%\\_done%");
+    }
+}